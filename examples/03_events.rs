@@ -1,10 +1,11 @@
-//! Event processing example: gestures, single-touch, multi-touch.
+//! Event processing example: gestures, single-touch, multi-touch and state
+//! changes.
 #![allow(unused)]
 use embedded_hal_async::{
   digital::Wait,
   i2c::{I2c, SevenBitAddress},
 };
-use iqs7211e::{Config, Event, Iqs7211e};
+use iqs7211e::{Config, Event, EventFilter, Iqs7211e};
 
 #[allow(dead_code)]
 async fn main_async<I2C, RDY, E>(i2c: I2C, rdy: RDY) -> Result<(), iqs7211e::Error<E>>
@@ -12,7 +13,7 @@ where
   I2C: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
 {
-  let mut dev = Iqs7211e::new(i2c, rdy, Config::default());
+  let mut dev = Iqs7211e::new(i2c, rdy, Config::default()).with_event_filter(EventFilter::all());
   let _ = dev.initialize().await?;
 
   loop {
@@ -29,6 +30,21 @@ where
         let _ = (primary, secondary, info);
         // handle two touches
       }
+      Event::Release => {
+        // all fingers lifted
+      }
+      Event::Motion(delta) => {
+        let _ = delta;
+        // move a cursor, see MotionAccumulator
+      }
+      Event::Proximity(near) => {
+        let _ = near;
+        // hand approached or left the pad
+      }
+      Event::DeviceReset => {
+        // device reset on its own; configuration was pushed again
+      }
+      Event::Retuned | Event::AutoTuneError | Event::TooManyFingers => {}
     }
   }
 }
@@ -0,0 +1,37 @@
+//! Blocking (executor-less) driver example.
+#![allow(unused)]
+use embedded_hal::{
+  digital::InputPin,
+  i2c::{I2c, SevenBitAddress},
+};
+use iqs7211e::{blocking, Config, Event};
+
+#[allow(dead_code)]
+fn main_blocking<I2C, RDY, E>(i2c: I2C, rdy: RDY) -> Result<(), iqs7211e::Error<E>>
+where
+  I2C: I2c<SevenBitAddress, Error = E>,
+  RDY: InputPin,
+{
+  let mut dev = blocking::Iqs7211e::new(i2c, rdy, Config::default());
+  let _ = dev.initialize()?;
+
+  // Methods without a blocking wrapper can still be driven to completion.
+  blocking::block_on(dev.as_async().trigger_autotune_for_alp())?;
+
+  loop {
+    match dev.next_event()? {
+      Event::Gesture(gesture, _) => {
+        let _ = gesture;
+      }
+      Event::Touch(primary, _) => {
+        let _ = primary;
+      }
+      Event::MultiTouch(primary, secondary, _) => {
+        let _ = (primary, secondary);
+      }
+      _ => {}
+    }
+  }
+}
+
+fn main() {}
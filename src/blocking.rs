@@ -0,0 +1,486 @@
+//! Blocking front-end for bare-metal superloops without an async executor.
+//!
+//! The driver logic is written once against the `embedded-hal-async` traits.
+//! This module adapts blocking `embedded-hal` 1.0 peripherals to those traits
+//! and drives each operation to completion on the spot, so the blocking and
+//! async flavours share the same [`Config`] model, bring-up sequence and event
+//! decoding.
+//!
+//! ```no_run
+//! use embedded_hal::{digital::InputPin, i2c::{I2c, SevenBitAddress}};
+//! use iqs7211e::{blocking, Config, Event};
+//!
+//! fn superloop<I2C, RDY, E>(i2c: I2C, rdy: RDY) -> Result<(), iqs7211e::Error<E>>
+//! where
+//!   I2C: I2c<SevenBitAddress, Error = E>,
+//!   RDY: InputPin,
+//! {
+//!   let mut controller = blocking::Iqs7211e::new(i2c, rdy, Config::default());
+//!   controller.initialize()?;
+//!   loop {
+//!     if let Event::Gesture(gesture, _) = controller.next_event()? {
+//!       let _ = gesture;
+//!     }
+//!   }
+//! }
+//! ```
+
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll};
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{self, InputPin, OutputPin};
+use embedded_hal::i2c::{self, Operation, SevenBitAddress};
+
+use crate::{
+  Alp, AutoTune, Calibration, ChannelOutput, ChannelTouchMap, ChargeMode, Config, ConfigSettings, ConversionFrequency,
+  Deadlines, DeviceIdentity, Error, Event, EventFilter, EventTriggers, FirmwarePolicy, Frame, Gesture, Gestures,
+  Hardware, Info, InterruptMode, NoDelay, NoReset, OtherSettings, PowerControl, PowerManager, PowerTransition,
+  ProductionLimits, ProductionReport, Recalibration, Recovery, SetupSession, Suspend, Timing, Touchpoints, Trackpad,
+  Vector, Version,
+};
+
+/// Granularity at which a blocking delay hands control back to [`block_on`]
+/// so the RDY pin keeps being sampled while a deadline runs.
+const DELAY_SLICE_US: u32 = 10;
+
+/// Adapter exposing a blocking `embedded-hal` peripheral through the async
+/// traits consumed by [`crate::Iqs7211e`].
+///
+/// I²C transfers are forwarded as-is. The RDY pin is busy-polled while waiting
+/// for a communication window, and delays are served in short slices so they
+/// can race RDY when a deadline is configured.
+#[derive(Debug)]
+pub struct Blocking<T>(pub T);
+
+/// Yield once to the executor.
+async fn yield_now() {
+  let mut yielded = false;
+  core::future::poll_fn(|_| match core::mem::replace(&mut yielded, true) {
+    true => Poll::Ready(()),
+    false => Poll::Pending,
+  })
+  .await
+}
+
+impl<T: i2c::ErrorType> i2c::ErrorType for Blocking<T> {
+  type Error = T::Error;
+}
+
+impl<T: i2c::I2c<SevenBitAddress>> embedded_hal_async::i2c::I2c<SevenBitAddress> for Blocking<T> {
+  async fn read(&mut self, address: SevenBitAddress, read: &mut [u8]) -> Result<(), Self::Error> {
+    self.0.read(address, read)
+  }
+
+  async fn write(&mut self, address: SevenBitAddress, write: &[u8]) -> Result<(), Self::Error> {
+    self.0.write(address, write)
+  }
+
+  async fn write_read(&mut self, address: SevenBitAddress, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+    self.0.write_read(address, write, read)
+  }
+
+  async fn transaction(
+    &mut self,
+    address: SevenBitAddress,
+    operations: &mut [Operation<'_>],
+  ) -> Result<(), Self::Error> {
+    self.0.transaction(address, operations)
+  }
+}
+
+impl<T: digital::ErrorType> digital::ErrorType for Blocking<T> {
+  type Error = T::Error;
+}
+
+impl<T: InputPin> embedded_hal_async::digital::Wait for Blocking<T> {
+  async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+    while !self.0.is_high()? {
+      yield_now().await;
+    }
+    Ok(())
+  }
+
+  async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+    while !self.0.is_low()? {
+      yield_now().await;
+    }
+    Ok(())
+  }
+
+  async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+    self.wait_for_low().await?;
+    self.wait_for_high().await
+  }
+
+  async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+    self.wait_for_high().await?;
+    self.wait_for_low().await
+  }
+
+  async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+    match self.0.is_high()? {
+      true => self.wait_for_low().await,
+      false => self.wait_for_high().await,
+    }
+  }
+}
+
+impl<T: DelayNs> embedded_hal_async::delay::DelayNs for Blocking<T> {
+  async fn delay_ns(&mut self, ns: u32) {
+    let mut remaining_us = ns.div_ceil(1_000);
+    while remaining_us > 0 {
+      let slice = remaining_us.min(DELAY_SLICE_US);
+      self.0.delay_us(slice);
+      remaining_us -= slice;
+      yield_now().await;
+    }
+  }
+}
+
+/// Drive a driver future to completion on the current thread.
+///
+/// Futures produced by a driver built on [`Blocking`] peripherals never wait on
+/// a waker, so this simply polls until completion. It is the escape hatch for
+/// calling any async method that has no blocking wrapper yet:
+/// `blocking::block_on(controller.as_async().trigger_autotune_for_alp())`.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+  let mut future = pin!(future);
+  let waker = crate::waker::noop_waker();
+  let mut cx = Context::from_waker(&waker);
+  loop {
+    if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+      return output;
+    }
+    core::hint::spin_loop();
+  }
+}
+
+/// Blocking flavour of [`crate::Iqs7211e`].
+///
+/// Wraps the async driver around [`Blocking`] adapters and exposes the same
+/// operations as plain functions. Methods without a blocking wrapper are
+/// reachable through [`Iqs7211e::as_async`] and [`block_on`].
+pub struct Iqs7211e<I, RDY, D = NoDelay, RST = NoReset> {
+  inner: crate::Iqs7211e<Blocking<I>, Blocking<RDY>, D, RST>,
+}
+
+impl<I, E, RDY> Iqs7211e<I, RDY>
+where
+  I: i2c::I2c<SevenBitAddress, Error = E>,
+  RDY: InputPin,
+{
+  /// Create a new blocking driver instance.
+  ///
+  /// See [`crate::Iqs7211e::new`].
+  pub fn new(i2c: I, rdy: RDY, config: Config) -> Self {
+    Self { inner: crate::Iqs7211e::new(Blocking(i2c), Blocking(rdy), config) }
+  }
+}
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: i2c::I2c<SevenBitAddress, Error = E>,
+  RDY: InputPin,
+  D: embedded_hal_async::delay::DelayNs,
+{
+  /// Attach a blocking delay provider used to time out unresponsive
+  /// communication.
+  ///
+  /// See [`crate::Iqs7211e::with_delay`].
+  pub fn with_delay<D2: DelayNs>(self, delay: D2) -> Iqs7211e<I, RDY, Blocking<D2>, RST> {
+    Iqs7211e { inner: self.inner.with_delay(Blocking(delay)) }
+  }
+
+  /// See [`crate::Iqs7211e::with_reset`].
+  pub fn with_reset<RST2: OutputPin>(self, reset: RST2) -> Iqs7211e<I, RDY, D, RST2> {
+    Iqs7211e { inner: self.inner.with_reset(reset) }
+  }
+
+  /// See [`crate::Iqs7211e::with_deadlines`].
+  pub fn with_deadlines(self, deadlines: Deadlines) -> Self {
+    Self { inner: self.inner.with_deadlines(deadlines) }
+  }
+
+  /// See [`crate::Iqs7211e::with_config_verification`].
+  pub fn with_config_verification(self, retries: u8) -> Self {
+    Self { inner: self.inner.with_config_verification(retries) }
+  }
+
+  /// See [`crate::Iqs7211e::with_firmware_policy`].
+  pub fn with_firmware_policy(self, policy: FirmwarePolicy) -> Self {
+    Self { inner: self.inner.with_firmware_policy(policy) }
+  }
+
+  /// See [`crate::Iqs7211e::with_event_filter`].
+  pub fn with_event_filter(self, filter: EventFilter) -> Self {
+    Self { inner: self.inner.with_event_filter(filter) }
+  }
+
+  /// Borrow the underlying async driver, e.g. to pass one of its futures to
+  /// [`block_on`].
+  pub fn as_async(&mut self) -> &mut crate::Iqs7211e<Blocking<I>, Blocking<RDY>, D, RST> {
+    &mut self.inner
+  }
+
+  /// See [`crate::Iqs7211e::initialize`].
+  pub fn initialize(&mut self) -> Result<bool, Error<E>> {
+    block_on(self.inner.initialize())
+  }
+
+  /// See [`crate::Iqs7211e::config`].
+  pub fn config(&self) -> &Config {
+    self.inner.config()
+  }
+
+  /// See [`crate::Iqs7211e::apply_gestures`].
+  pub fn apply_gestures(&mut self, gestures: Gestures) -> Result<(), Error<E>> {
+    block_on(self.inner.apply_gestures(gestures))
+  }
+
+  /// See [`crate::Iqs7211e::apply_timing`].
+  pub fn apply_timing(&mut self, timing: Timing) -> Result<(), Error<E>> {
+    block_on(self.inner.apply_timing(timing))
+  }
+
+  /// See [`crate::Iqs7211e::apply_channel_output`].
+  pub fn apply_channel_output(&mut self, channel_output: ChannelOutput) -> Result<(), Error<E>> {
+    block_on(self.inner.apply_channel_output(channel_output))
+  }
+
+  /// See [`crate::Iqs7211e::apply_trackpad`].
+  pub fn apply_trackpad(&mut self, trackpad: Trackpad) -> Result<(), Error<E>> {
+    block_on(self.inner.apply_trackpad(trackpad))
+  }
+
+  /// See [`crate::Iqs7211e::apply_alp`].
+  pub fn apply_alp(&mut self, alp: Alp) -> Result<(), Error<E>> {
+    block_on(self.inner.apply_alp(alp))
+  }
+
+  /// See [`crate::Iqs7211e::apply_auto_tune`].
+  pub fn apply_auto_tune(&mut self, auto_tune: AutoTune) -> Result<(), Error<E>> {
+    block_on(self.inner.apply_auto_tune(auto_tune))
+  }
+
+  /// See [`crate::Iqs7211e::apply_conversion_frequency`].
+  pub fn apply_conversion_frequency(&mut self, conversion: ConversionFrequency) -> Result<(), Error<E>> {
+    block_on(self.inner.apply_conversion_frequency(conversion))
+  }
+
+  /// See [`crate::Iqs7211e::apply_hardware`].
+  pub fn apply_hardware(&mut self, hardware: Hardware) -> Result<(), Error<E>> {
+    block_on(self.inner.apply_hardware(hardware))
+  }
+
+  /// See [`crate::Iqs7211e::apply_other_settings`].
+  pub fn apply_other_settings(&mut self, other_settings: OtherSettings) -> Result<(), Error<E>> {
+    block_on(self.inner.apply_other_settings(other_settings))
+  }
+
+  /// See [`crate::Iqs7211e::other_settings`].
+  pub fn other_settings(&mut self) -> Result<OtherSettings, Error<E>> {
+    block_on(self.inner.other_settings())
+  }
+
+  /// See [`crate::Iqs7211e::read_config`].
+  pub fn read_config(&mut self) -> Result<Config, Error<E>> {
+    block_on(self.inner.read_config())
+  }
+
+  /// See [`crate::Iqs7211e::verify_config`].
+  pub fn verify_config(&mut self, config: &Config) -> Result<(), Error<E>> {
+    block_on(self.inner.verify_config(config))
+  }
+
+  /// See [`crate::Iqs7211e::next_event`].
+  pub fn next_event(&mut self) -> Result<Event, Error<E>> {
+    block_on(self.inner.next_event())
+  }
+
+  /// See [`crate::Iqs7211e::touchpoints`].
+  pub fn touchpoints(&mut self) -> Result<Touchpoints, Error<E>> {
+    block_on(self.inner.touchpoints())
+  }
+
+  /// See [`crate::Iqs7211e::gesture`].
+  pub fn gesture(&mut self) -> Result<Gesture, Error<E>> {
+    block_on(self.inner.gesture())
+  }
+
+  /// See [`crate::Iqs7211e::relative_motion`].
+  pub fn relative_motion(&mut self) -> Result<Vector, Error<E>> {
+    block_on(self.inner.relative_motion())
+  }
+
+  /// See [`crate::Iqs7211e::channel_touch_map`].
+  pub fn channel_touch_map(&mut self) -> Result<ChannelTouchMap, Error<E>> {
+    block_on(self.inner.channel_touch_map())
+  }
+
+  /// See [`crate::Iqs7211e::capture_frame`].
+  pub fn capture_frame(&mut self) -> Result<Frame, Error<E>> {
+    block_on(self.inner.capture_frame())
+  }
+
+  /// See [`crate::Iqs7211e::production_test`].
+  pub fn production_test(&mut self, limits: &ProductionLimits) -> Result<ProductionReport, Error<E>> {
+    block_on(self.inner.production_test(limits))
+  }
+
+  /// See [`crate::Iqs7211e::info`].
+  pub fn info(&mut self) -> Result<Info, Error<E>> {
+    block_on(self.inner.info())
+  }
+
+  /// See [`crate::Iqs7211e::app_version`].
+  pub fn app_version(&mut self) -> Result<Version, Error<E>> {
+    block_on(self.inner.app_version())
+  }
+
+  /// See [`crate::Iqs7211e::rom_version`].
+  pub fn rom_version(&mut self) -> Result<Version, Error<E>> {
+    block_on(self.inner.rom_version())
+  }
+
+  /// See [`crate::Iqs7211e::device_identity`].
+  pub fn device_identity(&mut self) -> Result<DeviceIdentity, Error<E>> {
+    block_on(self.inner.device_identity())
+  }
+
+  /// See [`crate::Iqs7211e::identity`].
+  pub fn identity(&self) -> Option<&DeviceIdentity> {
+    self.inner.identity()
+  }
+
+  /// See [`crate::Iqs7211e::firmware_supported`].
+  pub fn firmware_supported(&self) -> bool {
+    self.inner.firmware_supported()
+  }
+
+  /// See [`crate::Iqs7211e::config_settings`].
+  pub fn config_settings(&mut self) -> Result<ConfigSettings, Error<E>> {
+    block_on(self.inner.config_settings())
+  }
+
+  /// See [`crate::Iqs7211e::set_interrupt_mode`].
+  pub fn set_interrupt_mode(&mut self, mode: InterruptMode) -> Result<(), Error<E>> {
+    block_on(self.inner.set_interrupt_mode(mode))
+  }
+
+  /// See [`crate::Iqs7211e::set_manual_control`].
+  pub fn set_manual_control(&mut self, enable: bool) -> Result<(), Error<E>> {
+    block_on(self.inner.set_manual_control(enable))
+  }
+
+  /// See [`crate::Iqs7211e::set_event_triggers`].
+  pub fn set_event_triggers(&mut self, triggers: EventTriggers) -> Result<(), Error<E>> {
+    block_on(self.inner.set_event_triggers(triggers))
+  }
+
+  /// See [`crate::Iqs7211e::set_watchdog`].
+  pub fn set_watchdog(&mut self, enable: bool) -> Result<(), Error<E>> {
+    block_on(self.inner.set_watchdog(enable))
+  }
+
+  /// See [`crate::Iqs7211e::set_end_comms`].
+  pub fn set_end_comms(&mut self, enable: bool) -> Result<(), Error<E>> {
+    block_on(self.inner.set_end_comms(enable))
+  }
+
+  /// See [`crate::Iqs7211e::set_charge_mode`].
+  pub fn set_charge_mode(&mut self, mode: ChargeMode) -> Result<(), Error<E>> {
+    block_on(self.inner.set_charge_mode(mode))
+  }
+
+  /// See [`crate::Iqs7211e::set_power_control`].
+  pub fn set_power_control(&mut self, control: PowerControl) -> Result<Info, Error<E>> {
+    block_on(self.inner.set_power_control(control))
+  }
+
+  /// See [`crate::PowerManager::on_system_idle`].
+  pub fn on_system_idle(&mut self, power: &mut PowerManager) -> Result<Option<PowerTransition>, Error<E>> {
+    block_on(power.on_system_idle(&mut self.inner))
+  }
+
+  /// See [`crate::PowerManager::on_system_active`].
+  pub fn on_system_active(&mut self, power: &mut PowerManager) -> Result<Option<PowerTransition>, Error<E>> {
+    block_on(power.on_system_active(&mut self.inner))
+  }
+
+  /// See [`crate::PowerManager::reapply`].
+  pub fn reapply_power(&mut self, power: &mut PowerManager) -> Result<Option<PowerTransition>, Error<E>> {
+    block_on(power.reapply(&mut self.inner))
+  }
+
+  /// See [`crate::Iqs7211e::ack_reset`].
+  pub fn ack_reset(&mut self) -> Result<(), Error<E>> {
+    block_on(self.inner.ack_reset())
+  }
+
+  /// See [`crate::Iqs7211e::trigger_autotune`].
+  pub fn trigger_autotune(&mut self) -> Result<(), Error<E>> {
+    block_on(self.inner.trigger_autotune())
+  }
+
+  /// See [`crate::Iqs7211e::software_reset`].
+  pub fn software_reset(&mut self) -> Result<(), Error<E>> {
+    block_on(self.inner.software_reset())
+  }
+
+  /// See [`crate::Iqs7211e::reseed_trackpad`].
+  pub fn reseed_trackpad(&mut self) -> Result<Calibration, Error<E>> {
+    block_on(self.inner.reseed_trackpad())
+  }
+
+  /// See [`crate::Iqs7211e::reseed_alp`].
+  pub fn reseed_alp(&mut self) -> Result<Calibration, Error<E>> {
+    block_on(self.inner.reseed_alp())
+  }
+
+  /// See [`crate::Iqs7211e::recalibrate`].
+  pub fn recalibrate(&mut self, kind: Recalibration) -> Result<Calibration, Error<E>> {
+    block_on(self.inner.recalibrate(kind))
+  }
+
+  /// See [`crate::Iqs7211e::suspend`].
+  pub fn suspend(&mut self, mode: Suspend) -> Result<(), Error<E>> {
+    block_on(self.inner.suspend(mode))
+  }
+
+  /// See [`crate::Iqs7211e::resume`].
+  pub fn resume(&mut self) -> Result<(), Error<E>> {
+    block_on(self.inner.resume())
+  }
+
+  /// See [`crate::Iqs7211e::suspended`].
+  pub fn suspended(&self) -> Option<Suspend> {
+    self.inner.suspended()
+  }
+
+  /// Begin an interactive setup sequence.
+  ///
+  /// The returned session is async; drive its steps with [`block_on`].
+  pub fn begin_setup(&mut self) -> SetupSession<'_, Blocking<I>, Blocking<RDY>, D, RST> {
+    self.inner.begin_setup()
+  }
+}
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: i2c::I2c<SevenBitAddress, Error = E>,
+  RDY: InputPin,
+  D: embedded_hal_async::delay::DelayNs,
+  RST: OutputPin,
+{
+  /// See [`crate::Iqs7211e::hardware_reset`].
+  pub fn hardware_reset(&mut self) -> Result<(), Error<E>> {
+    block_on(self.inner.hardware_reset())
+  }
+
+  /// See [`crate::Iqs7211e::recover`].
+  pub fn recover(&mut self) -> Result<Recovery, Error<E>> {
+    block_on(self.inner.recover())
+  }
+}
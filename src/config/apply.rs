@@ -0,0 +1,199 @@
+use core::ops::Range;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use super::auto_tune::ALP_COMPENSATION_LEN;
+use crate::{
+  Alp, AutoTune, ChannelOutput, ConversionFrequency, Error, Gestures, Hardware, Iqs7211e, OtherSettings, Reg, Timing,
+  Trackpad,
+};
+
+/// Bytes of the auto-tune window past the ALP compensation results.
+const TUNING_LEN: usize = 18 - ALP_COMPENSATION_LEN;
+/// Bytes of the tuning window holding the trackpad ATI parameters.
+const TRACKPAD_TUNE: Range<usize> = 0..6;
+/// Bytes of the tuning window holding the ALP ATI parameters.
+const ALP_TUNE: Range<usize> = 8..14;
+
+/// Engines whose ATI results are invalidated by a register change.
+#[derive(Debug, Clone, Copy)]
+struct Retune {
+  trackpad: bool,
+  alp: bool,
+}
+
+impl Retune {
+  const NONE: Self = Self { trackpad: false, alp: false };
+  const TRACKPAD: Self = Self { trackpad: true, alp: false };
+  const ALP: Self = Self { trackpad: false, alp: true };
+  const BOTH: Self = Self { trackpad: true, alp: true };
+
+  const fn union(self, other: Self) -> Self {
+    Self { trackpad: self.trackpad || other.trackpad, alp: self.alp || other.alp }
+  }
+}
+
+/// Auto-tune window without the ALP compensation results.
+fn tuning<E>(auto_tune: AutoTune) -> Result<[u8; TUNING_LEN], Error<E>> {
+  let bytes: [u8; 18] = auto_tune.try_into().map_err(|_| Error::BufferOverflow)?;
+  let mut tuning = [0; TUNING_LEN];
+  tuning.copy_from_slice(&bytes[ALP_COMPENSATION_LEN..]);
+  Ok(tuning)
+}
+
+/// Split used by windows holding a trackpad word followed by an ALP word.
+fn trackpad_then_alp(offset: usize) -> Retune {
+  match offset < 2 {
+    true => Retune::TRACKPAD,
+    false => Retune::ALP,
+  }
+}
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Replace the gesture settings on the running device.
+  pub async fn apply_gestures(&mut self, gestures: Gestures) -> Result<(), Error<E>> {
+    let next = self.config.with_gestures(gestures);
+    self
+      .apply(Reg::GestureEnable, self.config.gestures, next.gestures, |_| Retune::NONE)
+      .await?;
+    self.config = next;
+    Ok(())
+  }
+
+  /// Replace the report rates and mode timeouts on the running device.
+  pub async fn apply_timing(&mut self, timing: Timing) -> Result<(), Error<E>> {
+    let next = self.config.with_timing(timing);
+    self
+      .apply(Reg::ActiveModeReportRate, self.config.timing, next.timing, |_| Retune::NONE)
+      .await?;
+    self.config = next;
+    Ok(())
+  }
+
+  /// Replace the touch and ALP thresholds on the running device.
+  pub async fn apply_channel_output(&mut self, channel_output: ChannelOutput) -> Result<(), Error<E>> {
+    let next = self.config.with_channel_output(channel_output);
+    self
+      .apply(Reg::TouchSetClearMultipliers, self.config.channel_output, next.channel_output, |_| Retune::NONE)
+      .await?;
+    self.config = next;
+    Ok(())
+  }
+
+  /// Replace the trackpad settings (axes, filters, resolution) on the running
+  /// device. The Rx/Tx counts keep following the configured [`crate::Pinout`].
+  pub async fn apply_trackpad(&mut self, trackpad: Trackpad) -> Result<(), Error<E>> {
+    let next = self.config.with_trackpad(trackpad);
+    self
+      .apply(Reg::TpRxSettings, self.config.trackpad, next.trackpad, |_| Retune::NONE)
+      .await?;
+    self.config = next;
+    Ok(())
+  }
+
+  /// Replace the ALP setup on the running device, retuning ALP if it changed.
+  pub async fn apply_alp(&mut self, alp: Alp) -> Result<(), Error<E>> {
+    let next = self.config.with_alp(alp);
+    self
+      .apply(Reg::AlpSetup, self.config.alp, next.alp, |_| Retune::ALP)
+      .await?;
+    self.config = next;
+    Ok(())
+  }
+
+  /// Replace the ATI parameters on the running device, retuning each engine
+  /// whose parameters changed.
+  ///
+  /// The ALP compensation is not written: the device keeps the values found
+  /// by its last ALP ATI, and the staged ones only seed the ATI run by
+  /// [`Iqs7211e::initialize`].
+  pub async fn apply_auto_tune(&mut self, auto_tune: AutoTune) -> Result<(), Error<E>> {
+    let next = self.config.with_auto_tune(auto_tune);
+    let retune = |offset| match offset {
+      o if TRACKPAD_TUNE.contains(&o) => Retune::TRACKPAD,
+      o if ALP_TUNE.contains(&o) => Retune::ALP,
+      _ => Retune::NONE,
+    };
+    let (current, tuning) = (tuning(self.config.auto_tune)?, tuning(next.auto_tune)?);
+    self
+      .apply(Reg::TpAutoTuningMultipliers, current, tuning, retune)
+      .await?;
+    self.config = next;
+    Ok(())
+  }
+
+  /// Replace the conversion frequencies on the running device, retuning each
+  /// engine whose frequency changed.
+  pub async fn apply_conversion_frequency(&mut self, conversion: ConversionFrequency) -> Result<(), Error<E>> {
+    let next = self.config.with_conversion_frequency(conversion);
+    let current = self.config.conversion_frequency;
+    self
+      .apply(Reg::TpConvFreq, current, next.conversion_frequency, trackpad_then_alp)
+      .await?;
+    self.config = next;
+    Ok(())
+  }
+
+  /// Replace the analog front-end settings on the running device, retuning
+  /// each engine whose settings changed.
+  pub async fn apply_hardware(&mut self, hardware: Hardware) -> Result<(), Error<E>> {
+    let next = self.config.with_hardware(hardware);
+    self
+      .apply(Reg::TpHardware, self.config.hardware, next.hardware, trackpad_then_alp)
+      .await?;
+    self.config = next;
+    Ok(())
+  }
+
+  /// Replace the oscillator settings on the running device, retuning both
+  /// engines since every conversion frequency scales with the oscillator.
+  pub async fn apply_other_settings(&mut self, other_settings: OtherSettings) -> Result<(), Error<E>> {
+    let next = self.config.with_other_settings(other_settings);
+    self
+      .apply(Reg::OtherSettings, self.config.other_settings, next.other_settings, |_| Retune::BOTH)
+      .await?;
+    self.config = next;
+    Ok(())
+  }
+
+  /// Write one configuration window if it differs from the staged one, then
+  /// trigger ATI for the engines `retune` attributes the changed bytes to.
+  ///
+  /// Windows are forced in Event mode, one per access. ATI
+  /// runs in the background; completion shows up in [`crate::Info`].
+  async fn apply<const N: usize, T: TryInto<[u8; N]>>(
+    &mut self,
+    reg: Reg,
+    current: T,
+    next: T,
+    retune: impl Fn(usize) -> Retune,
+  ) -> Result<(), Error<E>> {
+    let current: [u8; N] = current.try_into().map_err(|_| Error::BufferOverflow)?;
+    let next: [u8; N] = next.try_into().map_err(|_| Error::BufferOverflow)?;
+
+    if current == next {
+      return Ok(());
+    }
+    let needed = (0..N)
+      .filter(|&i| current[i] != next[i])
+      .map(retune)
+      .fold(Retune::NONE, Retune::union);
+
+    self.open_comm_window().await?;
+    self.write_bytes(reg, &next).await?;
+    if needed.trackpad {
+      self.trigger_autotune().await?;
+    }
+    if needed.alp {
+      self.trigger_autotune_for_alp().await?;
+    }
+    Ok(())
+  }
+}
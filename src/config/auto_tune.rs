@@ -1,3 +1,7 @@
+/// Bytes of the auto-tune window holding the ALP compensation results, which
+/// the device overwrites on every ALP ATI.
+pub(crate) const ALP_COMPENSATION_LEN: usize = 4;
+
 #[derive(Debug, Clone, Copy)]
 #[packbits::pack(bytes = 18)]
 pub struct AutoTune {
@@ -243,7 +243,7 @@ pub struct SwipeConfig {
   distance_y: u16,
   consecutive_distance_x: u16,
   consecutive_distance_y: u16,
-  angle: u8,
+  pub(crate) angle: u8,
 }
 
 impl SwipeConfig {
@@ -0,0 +1,555 @@
+//! Import and export of the `IQS7211E_init.h` settings header used by the
+//! Azoteq GUI.
+
+use core::fmt;
+
+use super::SystemSettings;
+use crate::{Alp, Config, ConfigBlock, ConfigSettings, Pinout, Reg, SettingsVersion, Trackpad, MAX_CYCLES, MAX_PINS};
+
+/// First register covered by the header.
+const FIRST: Reg = Reg::AlpAutoTuningCompA;
+/// Bytes from [`FIRST`] up to the last cycle allocation byte.
+const IMAGE_LEN: usize = NAMED.len() + 3 * MAX_CYCLES;
+
+/// Defines of the header up to the cycle allocation, in memory order starting
+/// at [`FIRST`]. The cycle defines follow as `PLACEHOLDER_n`, `CH_1_CYCLE_n`
+/// and `CH_2_CYCLE_n` for each cycle `n`.
+const NAMED: [&str; 124] = [
+  "ALP_COMPENSATION_A_0",
+  "ALP_COMPENSATION_A_1",
+  "ALP_COMPENSATION_B_0",
+  "ALP_COMPENSATION_B_1",
+  "TP_ATI_MULTIPLIERS_DIVIDERS_0",
+  "TP_ATI_MULTIPLIERS_DIVIDERS_1",
+  "TP_COMPENSATION_DIV",
+  "TP_REF_DRIFT_LIMIT",
+  "TP_ATI_TARGET_0",
+  "TP_ATI_TARGET_1",
+  "TP_MIN_COUNT_REATI_0",
+  "TP_MIN_COUNT_REATI_1",
+  "ALP_ATI_MULTIPLIERS_DIVIDERS_0",
+  "ALP_ATI_MULTIPLIERS_DIVIDERS_1",
+  "ALP_COMPENSATION_DIV",
+  "ALP_LTA_DRIFT_LIMIT",
+  "ALP_ATI_TARGET_0",
+  "ALP_ATI_TARGET_1",
+  "ACTIVE_MODE_REPORT_RATE_0",
+  "ACTIVE_MODE_REPORT_RATE_1",
+  "IDLE_TOUCH_MODE_REPORT_RATE_0",
+  "IDLE_TOUCH_MODE_REPORT_RATE_1",
+  "IDLE_MODE_REPORT_RATE_0",
+  "IDLE_MODE_REPORT_RATE_1",
+  "LP1_MODE_REPORT_RATE_0",
+  "LP1_MODE_REPORT_RATE_1",
+  "LP2_MODE_REPORT_RATE_0",
+  "LP2_MODE_REPORT_RATE_1",
+  "ACTIVE_MODE_TIMEOUT_0",
+  "ACTIVE_MODE_TIMEOUT_1",
+  "IDLE_TOUCH_MODE_TIMEOUT_0",
+  "IDLE_TOUCH_MODE_TIMEOUT_1",
+  "IDLE_MODE_TIMEOUT_0",
+  "IDLE_MODE_TIMEOUT_1",
+  "LP1_MODE_TIMEOUT_0",
+  "LP1_MODE_TIMEOUT_1",
+  "REATI_RETRY_TIME",
+  "REF_UPDATE_TIME",
+  "I2C_TIMEOUT_0",
+  "I2C_TIMEOUT_1",
+  "SYSTEM_CONTROL_0",
+  "SYSTEM_CONTROL_1",
+  "CONFIG_SETTINGS0",
+  "CONFIG_SETTINGS1",
+  "OTHER_SETTINGS_0",
+  "OTHER_SETTINGS_1",
+  "ALP_SETUP_0",
+  "ALP_SETUP_1",
+  "ALP_TX_ENABLE_0",
+  "ALP_TX_ENABLE_1",
+  "TRACKPAD_TOUCH_SET_THRESHOLD",
+  "TRACKPAD_TOUCH_CLEAR_THRESHOLD",
+  "ALP_THRESHOLD_0",
+  "ALP_THRESHOLD_1",
+  "ALP_SET_DEBOUNCE",
+  "ALP_CLEAR_DEBOUNCE",
+  "ALP_COUNT_BETA_LP1",
+  "ALP_LTA_BETA_LP1",
+  "ALP_COUNT_BETA_LP2",
+  "ALP_LTA_BETA_LP2",
+  "TP_CONVERSION_FREQUENCY_UP_PASS_LENGTH",
+  "TP_CONVERSION_FREQUENCY_FRACTION_VALUE",
+  "ALP_CONVERSION_FREQUENCY_UP_PASS_LENGTH",
+  "ALP_CONVERSION_FREQUENCY_FRACTION_VALUE",
+  "TRACKPAD_HARDWARE_SETTINGS_0",
+  "TRACKPAD_HARDWARE_SETTINGS_1",
+  "ALP_HARDWARE_SETTINGS_0",
+  "ALP_HARDWARE_SETTINGS_1",
+  "TRACKPAD_SETTINGS_0_0",
+  "TRACKPAD_SETTINGS_0_1",
+  "TRACKPAD_SETTINGS_1_0",
+  "TRACKPAD_SETTINGS_1_1",
+  "X_RESOLUTION_0",
+  "X_RESOLUTION_1",
+  "Y_RESOLUTION_0",
+  "Y_RESOLUTION_1",
+  "XY_DYNAMIC_FILTER_BOTTOM_SPEED_0",
+  "XY_DYNAMIC_FILTER_BOTTOM_SPEED_1",
+  "XY_DYNAMIC_FILTER_TOP_SPEED_0",
+  "XY_DYNAMIC_FILTER_TOP_SPEED_1",
+  "XY_DYNAMIC_FILTER_BOTTOM_BETA",
+  "XY_DYNAMIC_FILTER_STATIC_FILTER_BETA",
+  "STATIONARY_TOUCH_MOV_THRESHOLD",
+  "FINGER_SPLIT_FACTOR",
+  "X_TRIM_VALUE",
+  "Y_TRIM_VALUE",
+  "MINOR_VERSION",
+  "MAJOR_VERSION",
+  "GESTURE_ENABLE_0",
+  "GESTURE_ENABLE_1",
+  "TAP_TOUCH_TIME_0",
+  "TAP_TOUCH_TIME_1",
+  "TAP_WAIT_TIME_0",
+  "TAP_WAIT_TIME_1",
+  "TAP_DISTANCE_0",
+  "TAP_DISTANCE_1",
+  "HOLD_TIME_0",
+  "HOLD_TIME_1",
+  "SWIPE_TIME_0",
+  "SWIPE_TIME_1",
+  "SWIPE_X_DISTANCE_0",
+  "SWIPE_X_DISTANCE_1",
+  "SWIPE_Y_DISTANCE_0",
+  "SWIPE_Y_DISTANCE_1",
+  "SWIPE_X_CONS_DIST_0",
+  "SWIPE_X_CONS_DIST_1",
+  "SWIPE_Y_CONS_DIST_0",
+  "SWIPE_Y_CONS_DIST_1",
+  "SWIPE_ANGLE",
+  "PALM_THRESHOLD",
+  "RX_TX_MAP_0",
+  "RX_TX_MAP_1",
+  "RX_TX_MAP_2",
+  "RX_TX_MAP_3",
+  "RX_TX_MAP_4",
+  "RX_TX_MAP_5",
+  "RX_TX_MAP_6",
+  "RX_TX_MAP_7",
+  "RX_TX_MAP_8",
+  "RX_TX_MAP_9",
+  "RX_TX_MAP_10",
+  "RX_TX_MAP_11",
+  "RX_TX_MAP_12",
+  "RX_TX_MAP_FILLER",
+];
+
+/// Comment block opening the header, as written by the Azoteq GUI.
+const PREAMBLE: &str = "\
+/******************************************************************************
+ * @file    IQS7211E_init.h
+ * @brief   This file contains all the necessary settings for the IQS7211E and\x20
+ *          this file can be changed from the GUI or edited here
+ * @author  Azoteq
+ *****************************************************************************/ \n";
+
+/// Header sections as `(title, first register, last register)`.
+const SECTIONS: [(&str, u8, u8); 15] = [
+  ("ALP ATI Compensation", 0x1F, 0x20),
+  ("ATI Settings", 0x21, 0x27),
+  ("Report Rates and Timing", 0x28, 0x32),
+  ("System Settings", 0x33, 0x35),
+  ("ALP Settings", 0x36, 0x37),
+  ("Thresholds and Debounce Settings", 0x38, 0x3A),
+  ("Button and ALP count and LTA betas", 0x3B, 0x3C),
+  ("Hardware Settings", 0x3D, 0x40),
+  ("Trackpad Settings", 0x41, 0x49),
+  ("Settings Version Numbers", 0x4A, 0x4A),
+  ("Gesture Settings", 0x4B, 0x55),
+  ("RxTx Mapping", 0x56, 0x5C),
+  ("Allocation of channels into cycles 0-9", 0x5D, 0x6B),
+  ("Allocation of channels into cycles 10-19", 0x6C, 0x7A),
+  ("Allocation of channels into cycles 20", 0x7B, 0x7C),
+];
+
+/// Configuration read from an Azoteq `IQS7211E_init.h` header.
+#[derive(Debug, Clone, Copy)]
+pub struct HeaderImport<'a> {
+  pub config: Config,
+  source: &'a str,
+}
+
+impl<'a> HeaderImport<'a> {
+  /// Defines with a value that are not device settings, e.g. ones added by
+  /// hand. They are ignored by the import.
+  pub fn unknown(&self) -> impl Iterator<Item = Define<'a>> + 'a {
+    defines(self.source).filter(|define| offset(define.name).is_none())
+  }
+}
+
+/// `#define NAME VALUE` line of a header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Define<'a> {
+  /// 1-based line number.
+  pub line: usize,
+  pub name: &'a str,
+  pub value: &'a str,
+}
+
+/// Reason a header could not be turned into a [`Config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderError {
+  /// A setting define does not hold a byte value.
+  InvalidValue { line: usize },
+  /// A setting is defined more than once.
+  Duplicate { line: usize },
+  /// No define sets this byte; `address` is the 8-bit register address and
+  /// `high` selects its upper byte.
+  Missing { address: u8, high: bool },
+  /// The bytes of a block do not decode, e.g. an out-of-range enum value.
+  InvalidBlock(ConfigBlock),
+}
+
+/// [`Config`] rendered as an Azoteq `IQS7211E_init.h` header, see
+/// [`Config::header`].
+#[derive(Debug, Clone, Copy)]
+pub struct HeaderExport<'a> {
+  config: &'a Config,
+}
+
+impl fmt::Display for HeaderExport<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let image = image(self.config).ok_or(fmt::Error)?;
+
+    writeln!(f, "{PREAMBLE}")?;
+    writeln!(f, "#ifndef IQS7211E_INIT_H")?;
+    writeln!(f, "#define IQS7211E_INIT_H")?;
+    for (title, first, last) in SECTIONS {
+      let start = usize::from(first - FIRST as u8) * 2;
+      let end = (usize::from(last - FIRST as u8 + 1) * 2).min(IMAGE_LEN);
+      writeln!(f)?;
+      writeln!(f, "/* Change the {title} */")?;
+      writeln!(f, "/* Memory Map Position 0x{first:02X} - 0x{last:02X} */")?;
+      for (at, value) in image.iter().enumerate().take(end).skip(start) {
+        write!(f, "#define ")?;
+        match NAMED.get(at) {
+          Some(name) => write!(f, "{name:<41}")?,
+          None => {
+            let (cycle, slot) = ((at - NAMED.len()) / 3, (at - NAMED.len()) % 3);
+            let name = ["PLACEHOLDER_", "CH_1_CYCLE_", "CH_2_CYCLE_"][slot];
+            write!(f, "{name}{cycle:<width$}", width = 41 - name.len())?;
+          }
+        }
+        writeln!(f, "0x{value:02X}")?;
+      }
+    }
+    write!(f, "\n#endif\t/* IQS7211E_INIT_H */")
+  }
+}
+
+impl Config {
+  /// Render the configuration as a settings header the Azoteq GUI and its
+  /// Arduino example accept.
+  ///
+  /// Bytes are laid out as [`Iqs7211e::initialize`](crate::Iqs7211e::initialize)
+  /// writes them, including the Rx/Tx mapping and cycle allocation of the
+  /// [`Pinout`], with the defines, sections and formatting of the vendor file.
+  /// The system settings carry the datasheet defaults, the configured
+  /// interrupt mode, event triggers, watchdog and end-comms option and
+  /// [`Config::other_settings`]; the settings version holds
+  /// [`Config::settings_stamp`].
+  ///
+  /// The result implements [`fmt::Display`], so it can be written to any
+  /// [`fmt::Write`] sink without allocation. Formatting fails if a block does
+  /// not encode.
+  pub fn header(&self) -> HeaderExport<'_> {
+    HeaderExport { config: self }
+  }
+
+  /// Parse a settings header exported by the Azoteq GUI.
+  ///
+  /// Every setting define from the ALP compensation (0x1F) to the last cycle
+  /// allocation register (0x7C) must be present. The [`Pinout`] is rebuilt
+  /// from the Rx/Tx mapping, the trackpad Rx/Tx counts and the ALP enable
+  /// masks; if the exported cycle allocation differs from the one the pinout
+  /// derives, it is kept through [`Pinout::with_cycles`]. The interrupt mode,
+  /// event triggers, watchdog and end-comms option follow `CONFIG_SETTINGS0/1`,
+  /// the oscillator settings `OTHER_SETTINGS_0` and the settings version
+  /// `MINOR_VERSION` / `MAJOR_VERSION`, imported as a
+  /// [`SettingsVersion::Fixed`] stamp; the remaining system settings are
+  /// recognised but not imported.
+  ///
+  /// Works without allocation, so it can run in a build script as well.
+  pub fn from_header(source: &str) -> Result<HeaderImport<'_>, HeaderError> {
+    let mut image = [0u8; IMAGE_LEN];
+    let mut seen = [false; IMAGE_LEN];
+
+    for define in defines(source) {
+      let Some(at) = offset(define.name) else { continue };
+      if seen[at] {
+        return Err(HeaderError::Duplicate { line: define.line });
+      }
+      image[at] = parse_byte(define.value).ok_or(HeaderError::InvalidValue { line: define.line })?;
+      seen[at] = true;
+    }
+    if let Some(at) = seen.iter().position(|seen| !seen) {
+      return Err(HeaderError::Missing { address: FIRST as u8 + (at / 2) as u8, high: at % 2 == 1 });
+    }
+
+    let trackpad: Trackpad = block(&image, Reg::TpRxSettings, ConfigBlock::Trackpad)?;
+    let alp: Alp = block(&image, Reg::AlpSetup, ConfigBlock::Alp)?;
+    let mapping: [u8; MAX_PINS + 1] = block(&image, Reg::RxTxMapping0_1, ConfigBlock::Mapping)?;
+    let mut pinout = Pinout::from_registers(&mapping, trackpad.total_rx, trackpad.total_tx, alp.rx, alp.tx)
+      .ok_or(HeaderError::InvalidBlock(ConfigBlock::Mapping))?;
+
+    let cycles: [u8; 3 * MAX_CYCLES] = block(&image, Reg::ProxACycle0, ConfigBlock::Cycles)?;
+    if pinout.cycles()[..cycles.len()] != cycles {
+      pinout = pinout.with_cycles(cycles);
+    }
+
+    let settings: ConfigSettings = block(&image, Reg::ConfigSettings, ConfigBlock::ConfigSettings)?;
+    let other_settings = block(&image, Reg::OtherSettings, ConfigBlock::OtherSettings)?;
+    let version: [u8; 2] = block(&image, Reg::SettingsVersion, ConfigBlock::SettingsVersion)?;
+
+    let config = Config::new(
+      block(&image, Reg::AlpAutoTuningCompA, ConfigBlock::AutoTune)?,
+      block(&image, Reg::ActiveModeReportRate, ConfigBlock::Timing)?,
+      alp,
+      block(&image, Reg::TouchSetClearMultipliers, ConfigBlock::ChannelOutput)?,
+      block(&image, Reg::TpConvFreq, ConfigBlock::ConversionFrequency)?,
+      block(&image, Reg::TpHardware, ConfigBlock::Hardware)?,
+      trackpad,
+      block(&image, Reg::GestureEnable, ConfigBlock::Gestures)?,
+      pinout,
+      settings.interrupt_mode,
+    )
+    .with_other_settings(other_settings)
+    .with_event_triggers(settings.event_triggers)
+    .with_watchdog(settings.watchdog)
+    .with_end_comms(settings.end_comms)
+    .with_settings_version(SettingsVersion::Fixed(u16::from_le_bytes(version)));
+    Ok(HeaderImport { config, source })
+  }
+}
+
+/// Header bytes from [`FIRST`], encoded as the device receives them.
+pub(super) fn image(config: &Config) -> Option<[u8; IMAGE_LEN]> {
+  let system = SystemSettings::new(config);
+
+  let mut image = [0u8; IMAGE_LEN];
+  put(&mut image, Reg::AlpAutoTuningCompA, config.auto_tune)?;
+  put(&mut image, Reg::ActiveModeReportRate, config.timing)?;
+  put(&mut image, Reg::SysControl, system)?;
+  put(&mut image, Reg::AlpSetup, config.alp)?;
+  put(&mut image, Reg::TouchSetClearMultipliers, config.channel_output)?;
+  put(&mut image, Reg::TpConvFreq, config.conversion_frequency)?;
+  put(&mut image, Reg::TpHardware, config.hardware)?;
+  put(&mut image, Reg::TpRxSettings, config.trackpad)?;
+  put(&mut image, Reg::GestureEnable, config.gestures)?;
+  put(&mut image, Reg::RxTxMapping0_1, config.pinout.mapping())?;
+  let cycles = config.pinout.cycles();
+  image[byte(Reg::ProxACycle0)..].copy_from_slice(&cycles[..3 * MAX_CYCLES]);
+  let version = config.settings_version.resolve(&image);
+  put(&mut image, Reg::SettingsVersion, version.to_le_bytes())?;
+  Some(image)
+}
+
+fn put<const N: usize, T: TryInto<[u8; N]>>(image: &mut [u8], reg: Reg, value: T) -> Option<()> {
+  let at = byte(reg);
+  image[at..at + N].copy_from_slice(&value.try_into().ok()?);
+  Some(())
+}
+
+fn defines(source: &str) -> impl Iterator<Item = Define<'_>> {
+  source.lines().enumerate().filter_map(|(index, line)| {
+    let mut words = line.split_whitespace();
+    if words.next()? != "#define" {
+      return None;
+    }
+    // Defines without a value, like the include guard, are not settings.
+    Some(Define { line: index + 1, name: words.next()?, value: words.next()? })
+  })
+}
+
+/// Byte offset of a setting define from [`FIRST`].
+fn offset(name: &str) -> Option<usize> {
+  if let Some(at) = NAMED.iter().position(|&named| named == name) {
+    return Some(at);
+  }
+  let (slot, cycle) = match name.rsplit_once('_')? {
+    ("PLACEHOLDER", cycle) => (0, cycle),
+    ("CH_1_CYCLE", cycle) => (1, cycle),
+    ("CH_2_CYCLE", cycle) => (2, cycle),
+    _ => return None,
+  };
+  let cycle: usize = cycle.parse().ok().filter(|&c| c < MAX_CYCLES)?;
+  Some(NAMED.len() + cycle * 3 + slot)
+}
+
+fn parse_byte(value: &str) -> Option<u8> {
+  match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+    Some(hex) => u8::from_str_radix(hex, 16).ok(),
+    None => value.parse().ok(),
+  }
+}
+
+pub(super) const fn byte(reg: Reg) -> usize {
+  (reg as usize - FIRST as usize) * 2
+}
+
+fn block<const N: usize, T: TryFrom<[u8; N]>>(image: &[u8], reg: Reg, name: ConfigBlock) -> Result<T, HeaderError> {
+  let at = byte(reg);
+  let bytes: [u8; N] = image[at..at + N]
+    .try_into()
+    .map_err(|_| HeaderError::InvalidBlock(name))?;
+  T::try_from(bytes).map_err(|_| HeaderError::InvalidBlock(name))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{EventTriggers, InterruptMode, MaxTouches};
+
+  const VENDOR: &str = include_str!("../../docs/iqs7211e-example-code/src/IQS7211E/IQS7211E_init.h");
+
+  /// Fixed-capacity text buffer standing in for `String`.
+  struct Text {
+    buf: [u8; 16384],
+    len: usize,
+  }
+
+  impl Text {
+    fn new() -> Self {
+      Self { buf: [0; 16384], len: 0 }
+    }
+
+    fn as_str(&self) -> &str {
+      core::str::from_utf8(&self.buf[..self.len]).unwrap()
+    }
+  }
+
+  impl core::fmt::Write for Text {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+      self.buf[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+      self.len += s.len();
+      Ok(())
+    }
+  }
+
+  /// `source` with the value of define `name` replaced, or the define removed
+  /// if `value` is empty.
+  fn edit(source: &str, name: &str, value: &str) -> Text {
+    use core::fmt::Write;
+    let mut out = Text::new();
+    for line in source.lines() {
+      let mut words = line.split_whitespace();
+      match (words.next(), words.next()) {
+        (Some("#define"), Some(define)) if define == name && value.is_empty() => {}
+        (Some("#define"), Some(define)) if define == name => writeln!(out, "#define {name} {value}").unwrap(),
+        _ => writeln!(out, "{line}").unwrap(),
+      }
+    }
+    out
+  }
+
+  fn append(source: &str, extra: &str) -> Text {
+    use core::fmt::Write;
+    let mut out = Text::new();
+    writeln!(out, "{source}").unwrap();
+    write!(out, "{extra}").unwrap();
+    out
+  }
+
+  #[test]
+  fn imports_vendor_header() {
+    let import = Config::from_header(VENDOR).unwrap();
+    let config = import.config;
+    assert_eq!(import.unknown().count(), 0);
+
+    assert_eq!(config.pinout.rx.len, 6);
+    assert_eq!(config.pinout.tx.len, 7);
+    assert_eq!(config.pinout.mapping(), [4, 5, 6, 2, 1, 0, 7, 8, 9, 10, 11, 12, 3, 0]);
+    assert!(matches!(config.trackpad.max_simultaneous_touches, MaxTouches::One));
+    assert_eq!((config.trackpad.resolution.x, config.trackpad.resolution.y), (1000, 1000));
+    assert_eq!(config.alp.rx, 0x77);
+    assert!(matches!(config.interrupt_mode, InterruptMode::Stream));
+    assert_eq!(config.event_triggers, EventTriggers::default());
+    assert!(config.watchdog && !config.end_comms);
+
+    // The exported allocation leaves channels out, so it is kept verbatim.
+    let cycles = config.pinout.cycles();
+    assert_eq!(cycles[..6], [0x05, 0x03, 0xFF, 0x05, 0xFF, 0xFF]);
+    assert_eq!(cycles[60..], [0x05, 0xFF, 0x26, 0x01]);
+
+    let gestures: [u8; 22] = config.gestures.try_into().unwrap();
+    assert_eq!(gestures[..4], [0x1F, 0xFF, 0x96, 0x00]);
+  }
+
+  #[test]
+  fn reports_unknown_defines() {
+    let header = append(VENDOR, "#define CUSTOM_BOARD_REV 0x02\n#define IQS_I2C_ADDR 0x56\n");
+    let import = Config::from_header(header.as_str()).unwrap();
+    let mut unknown = import.unknown();
+    assert_eq!(unknown.next().map(|d| (d.name, d.value)), Some(("CUSTOM_BOARD_REV", "0x02")));
+    assert_eq!(unknown.next().map(|d| d.name), Some("IQS_I2C_ADDR"));
+    assert!(unknown.next().is_none());
+  }
+
+  #[test]
+  fn rejects_incomplete_or_malformed_headers() {
+    let header = edit(VENDOR, "X_RESOLUTION_1", "0x103");
+    assert!(matches!(Config::from_header(header.as_str()), Err(HeaderError::InvalidValue { .. })));
+
+    let header = edit(VENDOR, "CH_2_CYCLE_4", "");
+    let missing = HeaderError::Missing { address: 0x5D + (4 * 3 + 2) / 2, high: false };
+    assert_eq!(Config::from_header(header.as_str()).unwrap_err(), missing);
+
+    let header = append(VENDOR, "#define SWIPE_ANGLE 0x20\n");
+    assert!(matches!(Config::from_header(header.as_str()), Err(HeaderError::Duplicate { .. })));
+  }
+
+  #[test]
+  fn exports_vendor_header_verbatim() {
+    use core::fmt::Write;
+    let config = Config::from_header(VENDOR).unwrap().config;
+    let mut header = Text::new();
+    write!(header, "{}", config.header()).unwrap();
+    assert_eq!(header.as_str(), VENDOR);
+  }
+
+  #[test]
+  fn round_trips_built_config() {
+    use crate::{Axes, AxesInset, MainOscillator, OtherSettings, Pin, Resolution, Swipe, Tap};
+    use core::fmt::Write;
+
+    let config = Config::default()
+      .with_pinout(Pinout::new(
+        [Pin::RxTx0, Pin::RxTx1, Pin::RxTx2, Pin::RxTx3],
+        [Pin::Tx8, Pin::Tx9, Pin::Tx10],
+        [Pin::RxTx0],
+        [Pin::Tx8],
+      ))
+      .with_trackpad(Trackpad::new().with_axes(Axes::default(), Resolution::new(640, 480), AxesInset::default()))
+      .with_gestures(
+        crate::Gestures::default()
+          .enable_tap(Tap::all())
+          .enable_swipe(Swipe::horizontal()),
+      )
+      .with_interrupt_mode(InterruptMode::Event)
+      .with_event_triggers(EventTriggers::new(true, false, true, true, false))
+      .with_end_comms(true)
+      .with_other_settings(OtherSettings::new(MainOscillator::Megahertz18, 3))
+      .with_settings_version(SettingsVersion::Hash);
+
+    let mut exported = Text::new();
+    write!(exported, "{}", config.header()).unwrap();
+    let imported = Config::from_header(exported.as_str()).unwrap().config;
+    assert!(matches!(imported.interrupt_mode, InterruptMode::Event));
+    assert_eq!(imported.event_triggers, config.event_triggers);
+    assert!(imported.watchdog && imported.end_comms);
+    assert_eq!(imported.other_settings, config.other_settings);
+    assert_eq!(imported.settings_version, SettingsVersion::Fixed(config.settings_stamp().unwrap()));
+    assert_eq!(image(&imported), image(&config));
+
+    let mut again = Text::new();
+    write!(again, "{}", imported.header()).unwrap();
+    assert_eq!(again.as_str(), exported.as_str());
+  }
+}
@@ -1,43 +1,63 @@
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 use embedded_hal_async::i2c::{I2c, SevenBitAddress};
 
-use crate::{ConfigSettings, Error, InterruptMode, Iqs7211e, Reg, SysControl};
+use crate::{ConfigSettings, Error, EventTriggers, InterruptMode, Iqs7211e, Reg, SysControl};
 
 mod alp;
+mod apply;
 mod auto_tune;
 mod conversion;
 mod gesture;
 mod hardware;
+mod header;
+mod oscillator;
 mod output;
 mod pinout;
+mod profile;
 mod timing;
 mod trackpad;
+mod validate;
+mod verify;
+mod version;
 
 pub use alp::*;
 pub use auto_tune::*;
 pub use conversion::*;
 pub use gesture::*;
 pub use hardware::*;
+pub use header::*;
+pub use oscillator::*;
 pub use output::*;
 pub use pinout::*;
+pub use profile::*;
 pub use timing::*;
 pub use trackpad::*;
+pub use validate::*;
+pub use verify::*;
+pub use version::*;
 
-impl<I, E, RDY> Iqs7211e<I, RDY>
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
 where
   I: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
+  D: DelayNs,
 {
   pub(crate) async fn write_config(&mut self, config: &Config) -> Result<(), Error<E>> {
     self.write(Reg::AlpAutoTuningCompA, config.auto_tune).await?;
     self.write(Reg::ActiveModeReportRate, config.timing).await?;
-    self.write(Reg::SysControl, SystemSettings::default()).await?;
+    // Stream mode with windows ending on STOP until `configure()` finishes
+    let mut system = SystemSettings::new(config);
+    system.config_settings.interrupt_mode = InterruptMode::Stream;
+    system.config_settings.end_comms = false;
+    self.write(Reg::SysControl, system).await?;
     self.write(Reg::AlpSetup, config.alp).await?;
     self.write(Reg::TouchSetClearMultipliers, config.channel_output).await?;
     self.write(Reg::TpConvFreq, config.conversion_frequency).await?;
     self.write(Reg::TpHardware, config.hardware).await?;
     self.write(Reg::TpRxSettings, config.trackpad).await?;
-    self.write_bytes(Reg::SettingsVersion, &[0, 0]).await?;
+    let stamp = config.settings_stamp().ok_or(Error::Encode)?;
+    self.write_bytes(Reg::SettingsVersion, &stamp.to_le_bytes()).await?;
     self.write(Reg::GestureEnable, config.gestures).await?;
     self.write_bytes(Reg::RxTxMapping0_1, &config.pinout.mapping()).await?;
 
@@ -48,6 +68,85 @@ where
 
     Ok(())
   }
+
+  /// Read SysControl and ConfigSettings in one transfer.
+  pub(crate) async fn system_settings(&mut self) -> Result<(SysControl, ConfigSettings), Error<E>> {
+    let [sys_lo, sys_hi, settings_lo, settings_hi]: [u8; 4] = self.read(Reg::SysControl).await?;
+    let sys_control = SysControl::try_from([sys_lo, sys_hi]).map_err(|_| Error::BufferOverflow)?;
+    let settings = ConfigSettings::try_from([settings_lo, settings_hi]).map_err(|_| Error::BufferOverflow)?;
+    Ok((sys_control, settings))
+  }
+
+  /// Bring a device that kept its settings across [`Iqs7211e::initialize`]
+  /// back to the staged system settings, given the ones it runs with. The
+  /// previous driver may have left it suspended, under manual control or with
+  /// other event triggers; only what differs is written.
+  pub(crate) async fn restore_system_settings(
+    &mut self,
+    mut sys_control: SysControl,
+    settings: ConfigSettings,
+  ) -> Result<(), Error<E>> {
+    if sys_control.suspend {
+      sys_control.suspend = false;
+      self.write(Reg::SysControl, sys_control).await?;
+    }
+    let encode = |settings: ConfigSettings| <[u8; 2]>::try_from(settings).map_err(|_| Error::BufferOverflow);
+    let staged = encode(SystemSettings::new(&self.config).config_settings)?;
+    if staged != encode(settings)? {
+      self.write_bytes(Reg::ConfigSettings, &staged).await?;
+    }
+    Ok(())
+  }
+
+  /// Read the configuration the device is currently running with.
+  ///
+  /// Every register window written by [`Iqs7211e::initialize`] is decoded back
+  /// into its typed block. The [`Pinout`] is rebuilt from the Rx/Tx mapping
+  /// registers, the Rx/Tx counts of the trackpad settings and the ALP enable
+  /// masks. The cycle allocation is read as well and kept through
+  /// [`Pinout::with_cycles`] when it differs from the derived one. Fails with
+  /// [`Error::Decode`] if the registers describe a pinout that cannot exist.
+  pub async fn read_config(&mut self) -> Result<Config, Error<E>> {
+    let auto_tune = self.read(Reg::AlpAutoTuningCompA).await?;
+    let timing = self.read(Reg::ActiveModeReportRate).await?;
+    let alp: Alp = self.read(Reg::AlpSetup).await?;
+    let channel_output = self.read(Reg::TouchSetClearMultipliers).await?;
+    let conversion_frequency = self.read(Reg::TpConvFreq).await?;
+    let hardware = self.read(Reg::TpHardware).await?;
+    let trackpad: Trackpad = self.read(Reg::TpRxSettings).await?;
+    let gestures = self.read(Reg::GestureEnable).await?;
+    let mapping = self.read(Reg::RxTxMapping0_1).await?;
+    let cycles: [u8; 3 * MAX_CYCLES] = self.read(Reg::ProxACycle0).await?;
+    let settings = self.config_settings().await?;
+    let other_settings = self.other_settings().await?;
+    let version = self.read_u16(Reg::SettingsVersion).await?;
+
+    let mut pinout =
+      Pinout::from_registers(&mapping, trackpad.total_rx, trackpad.total_tx, alp.rx, alp.tx).ok_or(Error::Decode)?;
+    if pinout.cycles()[..cycles.len()] != cycles {
+      pinout = pinout.with_cycles(cycles);
+    }
+
+    Ok(
+      Config::new(
+        auto_tune,
+        timing,
+        alp,
+        channel_output,
+        conversion_frequency,
+        hardware,
+        trackpad,
+        gestures,
+        pinout,
+        settings.interrupt_mode,
+      )
+      .with_other_settings(other_settings)
+      .with_event_triggers(settings.event_triggers)
+      .with_watchdog(settings.watchdog)
+      .with_end_comms(settings.end_comms)
+      .with_settings_version(SettingsVersion::Fixed(version)),
+    )
+  }
 }
 
 /// Complete touchpad configuration ready for device initialization.
@@ -87,10 +186,22 @@ pub struct Config {
   pub gestures: Gestures,
   pub pinout: Pinout,
   pub interrupt_mode: InterruptMode,
+  /// Events that open a window in Event mode.
+  pub event_triggers: EventTriggers,
+  /// Reset the device when I²C communication stalls.
+  pub watchdog: bool,
+  /// End communication windows with a command instead of the STOP condition.
+  /// The driver sends it after every report read by
+  /// [`Iqs7211e::next_event`], and otherwise before waiting for the next
+  /// window, so the last window of other calls stays open until then.
+  pub end_comms: bool,
+  pub other_settings: OtherSettings,
+  pub settings_version: SettingsVersion,
 }
 
 impl Config {
   /// Create a configuration seeded with the datasheet defaults.
+  #[allow(clippy::too_many_arguments)]
   pub const fn new(
     auto_tune: AutoTune,
     timing: Timing,
@@ -114,6 +225,11 @@ impl Config {
       gestures,
       pinout,
       interrupt_mode,
+      event_triggers: EventTriggers::new(true, true, false, false, false),
+      watchdog: true,
+      end_comms: false,
+      other_settings: OtherSettings::POWER_ON,
+      settings_version: SettingsVersion::Fixed(0),
     }
   }
 
@@ -176,6 +292,33 @@ impl Config {
     self.interrupt_mode = interrupt_mode;
     self
   }
+
+  pub const fn with_event_triggers(mut self, event_triggers: EventTriggers) -> Self {
+    self.event_triggers = event_triggers;
+    self
+  }
+
+  pub const fn with_watchdog(mut self, watchdog: bool) -> Self {
+    self.watchdog = watchdog;
+    self
+  }
+
+  pub const fn with_end_comms(mut self, end_comms: bool) -> Self {
+    self.end_comms = end_comms;
+    self
+  }
+
+  pub const fn with_other_settings(mut self, other_settings: OtherSettings) -> Self {
+    self.other_settings = other_settings;
+    self
+  }
+
+  /// Stamp the settings so warm boots can skip the push, see
+  /// [`Iqs7211e::initialize`].
+  pub const fn with_settings_version(mut self, version: SettingsVersion) -> Self {
+    self.settings_version = version;
+    self
+  }
 }
 
 impl Default for Config {
@@ -195,6 +338,19 @@ impl Default for Config {
   }
 }
 
+/// 4×3 trackpad with the ALP on its corner channel, shared by the tests.
+#[cfg(test)]
+pub(crate) fn test_config() -> Config {
+  use crate::Pin;
+
+  Config::default().with_pinout(Pinout::new(
+    [Pin::RxTx0, Pin::RxTx2, Pin::RxTx4, Pin::RxTx6],
+    [Pin::Tx8, Pin::Tx9, Pin::Tx10],
+    [Pin::RxTx0],
+    [Pin::Tx8],
+  ))
+}
+
 #[derive(Debug, Clone, Copy, Default)]
 #[packbits::pack(bytes = 6)]
 struct SystemSettings {
@@ -202,5 +358,20 @@ struct SystemSettings {
   sys_control: SysControl,
   #[bits(16)]
   config_settings: ConfigSettings,
-  other_settings: u16,
+  #[bits(16)]
+  other_settings: OtherSettings,
+}
+
+impl SystemSettings {
+  /// Settings block staged in `config`.
+  fn new(config: &Config) -> Self {
+    let config_settings = ConfigSettings {
+      interrupt_mode: config.interrupt_mode,
+      event_triggers: config.event_triggers,
+      watchdog: config.watchdog,
+      end_comms: config.end_comms,
+      ..ConfigSettings::default()
+    };
+    Self { config_settings, other_settings: config.other_settings, ..Self::default() }
+  }
 }
@@ -0,0 +1,79 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::{Error, Iqs7211e, Reg};
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Fetch the oscillator settings the device is running with.
+  pub async fn other_settings(&mut self) -> Result<OtherSettings, Error<E>> {
+    self.read(Reg::OtherSettings).await
+  }
+}
+
+/// Main oscillator settings (0x35). Bits 15–5 are unused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[packbits::pack(u16)]
+pub struct OtherSettings {
+  /// Small downward trim of the main oscillator, 0 (none) to 15 (maximum).
+  #[bits(4)]
+  pub main_osc_adjust: u8,
+  #[bits(1)]
+  pub main_oscillator: MainOscillator,
+}
+
+impl OtherSettings {
+  /// Power-on setting: 14 MHz without trim.
+  pub(crate) const POWER_ON: Self = Self::new(MainOscillator::Megahertz14, 0);
+
+  pub const fn new(main_oscillator: MainOscillator, main_osc_adjust: u8) -> Self {
+    Self { main_osc_adjust, main_oscillator }
+  }
+
+  pub const fn with_main_oscillator(mut self, main_oscillator: MainOscillator) -> Self {
+    self.main_oscillator = main_oscillator;
+    self
+  }
+
+  pub const fn with_main_osc_adjust(mut self, main_osc_adjust: u8) -> Self {
+    self.main_osc_adjust = main_osc_adjust;
+    self
+  }
+}
+
+impl Default for OtherSettings {
+  fn default() -> Self {
+    Self::POWER_ON
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MainOscillator {
+  Megahertz14 = 0,
+  /// Raises the minimum supply voltage, see the datasheet operating
+  /// conditions.
+  Megahertz18 = 1,
+}
+
+impl From<MainOscillator> for u8 {
+  fn from(v: MainOscillator) -> Self {
+    v as u8
+  }
+}
+
+impl TryFrom<u8> for MainOscillator {
+  type Error = ();
+
+  fn try_from(bits: u8) -> Result<Self, Self::Error> {
+    match bits & 0b1 {
+      0 => Ok(Self::Megahertz14),
+      1 => Ok(Self::Megahertz18),
+      _ => Err(()),
+    }
+  }
+}
@@ -1,8 +1,10 @@
 pub(crate) const MAX_PINS: usize = 13;
 pub(crate) const MAX_CYCLES: usize = 21;
 pub(crate) const UNUSED_CYCLE: u8 = 0xFF;
+/// Bytes of the cycle allocation registers (0x5D..0x7C), terminator included.
+const CYCLE_BYTES: usize = 64;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Pin {
   RxTx0 = 0,
   RxTx1 = 1,
@@ -19,7 +21,35 @@ pub enum Pin {
   Tx12 = 12,
 }
 
+impl TryFrom<u8> for Pin {
+  type Error = ();
+
+  fn try_from(value: u8) -> Result<Self, Self::Error> {
+    match value {
+      0 => Ok(Self::RxTx0),
+      1 => Ok(Self::RxTx1),
+      2 => Ok(Self::RxTx2),
+      3 => Ok(Self::RxTx3),
+      4 => Ok(Self::RxTx4),
+      5 => Ok(Self::RxTx5),
+      6 => Ok(Self::RxTx6),
+      7 => Ok(Self::RxTx7),
+      8 => Ok(Self::Tx8),
+      9 => Ok(Self::Tx9),
+      10 => Ok(Self::Tx10),
+      11 => Ok(Self::Tx11),
+      12 => Ok(Self::Tx12),
+      _ => Err(()),
+    }
+  }
+}
+
 impl Pin {
+  /// Whether the pin can receive, i.e. is one of RxTx0..RxTx7.
+  pub(crate) fn is_rx(self) -> bool {
+    self.is_a() || self.is_b()
+  }
+
   fn is_a(self) -> bool {
     (self as u8) < 4
   }
@@ -46,10 +76,26 @@ impl Pins {
     Self { pins: full, len: N }
   }
 
-  fn pins(&self) -> &[Pin] {
+  pub(crate) fn pins(&self) -> &[Pin] {
     &self.pins[..self.len]
   }
 
+  /// Collect pins from register contents, rejecting unknown pin numbers.
+  fn decode(raw: impl IntoIterator<Item = u8>) -> Option<Self> {
+    let mut out = Self::new([]);
+    for value in raw {
+      *out.pins.get_mut(out.len)? = Pin::try_from(value).ok()?;
+      out.len += 1;
+    }
+    Some(out)
+  }
+
+  /// Pins whose bit is set in an enable mask, in ascending order.
+  fn from_mask(mask: u16) -> Self {
+    let set = (0..MAX_PINS as u8).filter(|&bit| mask & (1 << bit) != 0);
+    Self::decode(set).unwrap_or(Self::new([]))
+  }
+
   const fn is_subset_of(&self, other: Pins) -> bool {
     let mut i = 0;
     while i < self.len {
@@ -61,7 +107,7 @@ impl Pins {
     true
   }
 
-  const fn contains(&self, pin: Pin) -> bool {
+  pub(crate) const fn contains(&self, pin: Pin) -> bool {
     let mut i = 0;
     while i < self.len {
       if self.pins[i] as u8 == pin as u8 {
@@ -80,6 +126,8 @@ pub struct Pinout {
   pub tx: Pins,
   pub alp_rx: Pins,
   pub alp_tx: Pins,
+  /// Cycle allocation overriding the one derived from the pins.
+  cycles: Option<[u8; CYCLE_BYTES]>,
 }
 
 impl Pinout {
@@ -96,13 +144,14 @@ impl Pinout {
     let tx = Pins::new(tx);
     assert!(alp_rx.is_subset_of(rx), "ALP rx should be a subset of main rx");
     assert!(alp_tx.is_subset_of(tx), "ALP tx should be a subset of main tx");
-    Self { rx, tx, alp_rx, alp_tx }
+    Self { rx, tx, alp_rx, alp_tx, cycles: None }
   }
 
   pub const fn with_rxtx<const RX: usize, const TX: usize>(mut self, rx: [Pin; RX], tx: [Pin; TX]) -> Self {
     assert!(RX + TX <= MAX_PINS, "maximum 13 total Rx/Tx pins");
     self.rx = Pins::new(rx);
     self.tx = Pins::new(tx);
+    self.cycles = None;
     assert!(self.alp_rx.is_subset_of(self.rx), "ALP rx should be a subset of main rx");
     assert!(self.alp_tx.is_subset_of(self.tx), "ALP tx should be a subset of main tx");
     self
@@ -116,6 +165,45 @@ impl Pinout {
     self
   }
 
+  /// Use an explicit cycle allocation instead of deriving one from the pins,
+  /// e.g. one exported by the Azoteq GUI that leaves some channels unused.
+  ///
+  /// `cycles` holds the cycle allocation registers (0x5D..0x7C) byte by byte:
+  /// `[0x05, prox_a, prox_b]` per cycle. Replacing the Rx/Tx pins afterwards
+  /// drops the override.
+  pub const fn with_cycles(mut self, cycles: [u8; 3 * MAX_CYCLES]) -> Self {
+    let mut bytes = [0; CYCLE_BYTES];
+    let mut i = 0;
+    while i < cycles.len() {
+      bytes[i] = cycles[i];
+      i += 1;
+    }
+    bytes[3 * MAX_CYCLES] = 0x01;
+    self.cycles = Some(bytes);
+    self
+  }
+
+  /// Rebuild a pinout from the Rx/Tx mapping registers (0x56..0x5C), the
+  /// Rx/Tx counts of the trackpad settings and the ALP enable masks.
+  ///
+  /// Returns `None` if the mapping references a pin that does not exist, or the
+  /// ALP masks enable a pin outside the trackpad Rx/Tx.
+  pub(crate) fn from_registers(mapping: &[u8; MAX_PINS + 1], rx: u8, tx: u8, alp_rx: u8, alp_tx: u16) -> Option<Self> {
+    let (rx, tx) = (rx as usize, tx as usize);
+    if rx + tx > MAX_PINS {
+      return None;
+    }
+    let pinout = Self {
+      rx: Pins::decode(mapping[..rx].iter().copied())?,
+      tx: Pins::decode(mapping[rx..rx + tx].iter().copied())?,
+      alp_rx: Pins::from_mask(alp_rx as u16),
+      alp_tx: Pins::from_mask(alp_tx),
+      cycles: None,
+    };
+    let nested = pinout.alp_rx.is_subset_of(pinout.rx) && pinout.alp_tx.is_subset_of(pinout.tx);
+    nested.then_some(pinout)
+  }
+
   pub(crate) fn mapping(&self) -> [u8; MAX_PINS + 1] {
     let mut out = [0; MAX_PINS + 1];
     for (idx, &pin) in self.rx.pins().iter().enumerate() {
@@ -137,7 +225,13 @@ impl Pinout {
   /// The returned byte array is laid out exactly as expected by the cycle allocation registers (0x5D–0x7C): each cycle
   /// contributes three bytes `[0x05, prox_a, prox_b]`, and a trailing terminator byte (0x01) fills the final high byte
   /// of  the register window.
-  pub(crate) fn cycles(&self) -> [u8; 64] {
+  ///
+  /// An allocation set with [`Pinout::with_cycles`] is returned as is.
+  pub(crate) fn cycles(&self) -> [u8; CYCLE_BYTES] {
+    if let Some(cycles) = self.cycles {
+      return cycles;
+    }
+
     // Build cycles as (tx, prox_a_channel, prox_b_channel) tuples first
     let mut cycles = [(0u8, UNUSED_CYCLE, UNUSED_CYCLE); MAX_CYCLES];
     let mut cycle_count = 0usize;
@@ -184,7 +278,7 @@ impl Pinout {
     }
 
     // Convert to register byte format: [0x05, prox_a, prox_b] per cycle
-    let mut bytes = [0; 64];
+    let mut bytes = [0; CYCLE_BYTES];
     for i in 0..MAX_CYCLES {
       bytes[i * 3] = 0x05;
       bytes[i * 3 + 1] = cycles[i].1;
@@ -194,6 +288,18 @@ impl Pinout {
     bytes
   }
 
+  /// Cycles the derived allocation needs, which may exceed the 21 available,
+  /// or `None` with an allocation set through [`Pinout::with_cycles`].
+  pub(crate) fn required_cycles(&self) -> Option<usize> {
+    if self.cycles.is_some() {
+      return None;
+    }
+    // Every Tx needs as many cycles as its busier prox block has channels
+    let a = self.rx.pins().iter().filter(|pin| pin.is_a()).count();
+    let b = self.rx.pins().iter().filter(|pin| pin.is_b()).count();
+    Some(self.tx.len * a.max(b))
+  }
+
   pub(crate) const fn alp_rx(&self) -> u8 {
     self.alp_rx.contains(Pin::RxTx0) as u8
       | (self.alp_rx.contains(Pin::RxTx1) as u8) << 1
@@ -292,6 +398,38 @@ mod tests {
     assert_eq!(m[4..7], [8u8, 9u8, 10u8]);
   }
 
+  #[test]
+  fn from_registers_restores_layout() {
+    let layout = Pinout::new([Pin::RxTx0, Pin::RxTx2, Pin::RxTx4, Pin::RxTx6], [Pin::Tx8, Pin::Tx9, Pin::Tx10], [], [])
+      .with_alp_rxtx([Pin::RxTx0, Pin::RxTx4], [Pin::Tx8, Pin::Tx10]);
+
+    let decoded = Pinout::from_registers(&layout.mapping(), 4, 3, layout.alp_rx(), layout.alp_tx()).unwrap();
+
+    assert_eq!(decoded.mapping(), layout.mapping());
+    assert_eq!(decoded.cycles(), layout.cycles());
+    assert_eq!((decoded.alp_rx(), decoded.alp_tx()), (layout.alp_rx(), layout.alp_tx()));
+    assert!(decoded.alp_tx.contains(Pin::Tx10));
+    assert!(!decoded.alp_tx.contains(Pin::Tx9));
+  }
+
+  #[test]
+  fn from_registers_rejects_unknown_pins() {
+    let mut mapping = [0u8; MAX_PINS + 1];
+    mapping[1] = 13;
+    assert!(Pinout::from_registers(&mapping, 2, 1, 0, 0).is_none());
+    assert!(Pinout::from_registers(&mapping, 10, 4, 0, 0).is_none());
+  }
+
+  #[test]
+  fn from_registers_rejects_alp_pins_outside_the_trackpad() {
+    let layout = Pinout::new([Pin::RxTx0, Pin::RxTx2], [Pin::Tx8], [], []);
+    let mapping = layout.mapping();
+
+    assert!(Pinout::from_registers(&mapping, 2, 1, 1 << 0, 1 << 8).is_some());
+    assert!(Pinout::from_registers(&mapping, 2, 1, 1 << 4, 0).is_none());
+    assert!(Pinout::from_registers(&mapping, 2, 1, 0, 1 << 9).is_none());
+  }
+
   #[test]
   fn alp_subset_ok() {
     let layout_ok =
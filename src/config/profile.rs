@@ -0,0 +1,297 @@
+use crate::{AutoProxCycles, ChargeMode, Config, Frequency, MainOscillator};
+
+use super::{MAX_CYCLES, UNUSED_CYCLE};
+
+// Typical figures behind the estimates, derived from the datasheet's current
+// consumption and timing characteristics. They are meant for comparing
+// configurations, not for predicting a particular board.
+/// Current between report cycles, with the core and the oscillator asleep, in nA.
+const SLEEP_NA: u64 = 1_500;
+/// Core current per MHz of main oscillator while it converts or processes, in nA.
+const RUN_NA_PER_MHZ: u64 = 130_000;
+/// Core time spent on one report cycle besides the conversions, in µs.
+const PROCESSING_US: u64 = 400;
+/// Current of an autonomous ALP conversion while the core sleeps, in nA.
+const AUTO_PROX_NA: u64 = 250_000;
+
+const MODES: [ChargeMode; 5] = [
+  ChargeMode::Active,
+  ChargeMode::IdleTouch,
+  ChargeMode::Idle,
+  ChargeMode::LowPower1,
+  ChargeMode::LowPower2,
+];
+
+impl Config {
+  /// Estimate the current drawn in each [`ChargeMode`] and the latency of a
+  /// first touch from LP2.
+  ///
+  /// A conversion takes as many charge transfers as the ATI target of its
+  /// engine, at the transfer frequency of [`crate::ConversionFrequency`]. The
+  /// trackpad runs one conversion per allocated cycle of [`Pinout`](crate::Pinout),
+  /// while LP1 and LP2 only convert the ALP channel and wake the core once
+  /// per auto-prox burst. Without ALP pins the trackpad is sensed in every
+  /// mode.
+  pub fn power_profile(&self) -> PowerProfile {
+    let run_na = RUN_NA_PER_MHZ * self.oscillator_mhz();
+    let cycles = self.pinout.cycles();
+    let trackpad_cycles = (0..MAX_CYCLES)
+      .filter(|&cycle| cycles[cycle * 3 + 1] != UNUSED_CYCLE || cycles[cycle * 3 + 2] != UNUSED_CYCLE)
+      .count() as u64;
+    let trackpad_us =
+      trackpad_cycles * self.conversion_us(self.conversion_frequency.trackpad, self.auto_tune.tune.target);
+    let has_alp = self.alp.rx != 0;
+    let alp_us = if has_alp {
+      self.conversion_us(self.conversion_frequency.alp, self.auto_tune.alp_tune.target)
+    } else {
+      0
+    };
+
+    let rates = self.timing.report_rate;
+    let periods = [rates.active, rates.idle_touch, rates.idle, rates.lp1, rates.lp2];
+    let mut modes = [ModeEstimate { report_period_ms: 0, busy_us: 0, current_na: 0 }; MODES.len()];
+    for (index, mode) in MODES.into_iter().enumerate() {
+      let period_us = u64::from(periods[index].max(1)) * 1000;
+      let auto_prox = match mode {
+        ChargeMode::LowPower1 => Some(self.hardware.alp.lp1_auto_prox_cycles),
+        ChargeMode::LowPower2 => Some(self.hardware.alp.lp2_auto_prox_cycles),
+        _ => None,
+      };
+      let (busy_us, charge) = if let (true, Some(auto_prox)) = (has_alp, auto_prox) {
+        // One core wake-up per burst, the other conversions run autonomously
+        let burst = burst_len(auto_prox);
+        let busy_us = PROCESSING_US + alp_us;
+        let charge = busy_us * run_na + (burst - 1) * alp_us * AUTO_PROX_NA;
+        (busy_us, charge / burst)
+      } else {
+        let busy_us = PROCESSING_US + trackpad_us + alp_us;
+        (busy_us, busy_us * run_na)
+      };
+      let current_na = (SLEEP_NA + charge / period_us).min(run_na);
+      modes[index] =
+        ModeEstimate { report_period_ms: periods[index], busy_us: busy_us as u32, current_na: current_na as u32 };
+    }
+
+    // The touch lands just after an LP2 sample; the next one detects it and
+    // the trackpad reports it on the first Active cycle.
+    let lp2 = modes[ChargeMode::LowPower2 as usize];
+    let active = modes[ChargeMode::Active as usize];
+    let first_touch_latency_us = if has_alp {
+      u32::from(lp2.report_period_ms) * 1000 + lp2.busy_us + u32::from(active.report_period_ms) * 1000 + active.busy_us
+    } else {
+      u32::from(lp2.report_period_ms) * 1000 + lp2.busy_us
+    };
+
+    PowerProfile { modes, timeouts: self.timing.timeouts, first_touch_latency_us }
+  }
+
+  fn oscillator_mhz(&self) -> u64 {
+    match self.other_settings.main_oscillator {
+      MainOscillator::Megahertz14 => 14,
+      MainOscillator::Megahertz18 => 18,
+    }
+  }
+
+  /// Duration of one conversion of `counts` charge transfers, in µs.
+  fn conversion_us(&self, frequency: Frequency, counts: u16) -> u64 {
+    // f = clk · fraction / 256 / (period + 2), e.g. 250 kHz for period 26 at
+    // fraction 127 on the 14 MHz oscillator
+    let clock_hz = self.oscillator_mhz() * 1_000_000;
+    let transfer_hz = (clock_hz * u64::from(frequency.fraction) / 256 / (u64::from(frequency.period) + 2)).max(1);
+    u64::from(counts) * 1_000_000 / transfer_hz
+  }
+}
+
+fn burst_len(cycles: AutoProxCycles) -> u64 {
+  match cycles {
+    AutoProxCycles::Cycles4 => 4,
+    AutoProxCycles::Cycles8 => 8,
+    AutoProxCycles::Cycles16 => 16,
+    AutoProxCycles::Cycles32 => 32,
+    AutoProxCycles::Disabled => 1,
+  }
+}
+
+/// Estimated cost of one [`ChargeMode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModeEstimate {
+  pub report_period_ms: u16,
+  /// Time the core runs per report cycle, in µs.
+  pub busy_us: u32,
+  /// Average current, in nA.
+  pub current_na: u32,
+}
+
+/// Estimates of [`Config::power_profile`].
+#[derive(Debug, Clone, Copy)]
+pub struct PowerProfile {
+  modes: [ModeEstimate; MODES.len()],
+  timeouts: crate::Timeouts,
+  /// Worst case from a touch on a device in LP2 to its first report, in µs.
+  pub first_touch_latency_us: u32,
+}
+
+impl PowerProfile {
+  pub fn mode(&self, mode: ChargeMode) -> ModeEstimate {
+    self.modes[mode as usize]
+  }
+
+  /// Average current in `mode`, in nA.
+  pub fn current_na(&self, mode: ChargeMode) -> u32 {
+    self.mode(mode).current_na
+  }
+
+  /// Split one repetition of `usage` into the modes the device steps through
+  /// on its [`crate::Timeouts`], and average the current over it.
+  ///
+  /// Movement keeps the device in Active. A resting finger moves it to
+  /// IdleTouch after the Active timeout, and to Idle once the IdleTouch
+  /// timeout reseeds it away. Once the finger lifts the device stays in Active
+  /// for its timeout and then steps down through Idle and LP1 to LP2. A
+  /// timeout of 0 never expires.
+  pub fn usage(&self, usage: &UsageModel) -> UsageEstimate {
+    let timeouts = self.timeouts;
+    let mut time_ms = [0; MODES.len()];
+    time_ms[ChargeMode::Active as usize] = usage.touch_ms;
+
+    let mut hold = Phase { remaining_ms: usage.hold_ms, time_ms: &mut time_ms };
+    hold.spend(ChargeMode::Active, timeouts.active);
+    hold.spend(ChargeMode::IdleTouch, timeouts.idle_touch);
+    hold.spend(ChargeMode::Idle, 0);
+
+    let mut away = Phase { remaining_ms: usage.away_ms, time_ms: &mut time_ms };
+    away.spend(ChargeMode::Active, timeouts.active);
+    away.spend(ChargeMode::Idle, timeouts.idle);
+    away.spend(ChargeMode::LowPower1, timeouts.lp1);
+    away.spend(ChargeMode::LowPower2, 0);
+
+    let total_ms: u64 = time_ms.iter().map(|&ms| u64::from(ms)).sum();
+    let average_current_na = match total_ms {
+      0 => self.current_na(ChargeMode::Active),
+      total_ms => {
+        let charge: u64 = MODES
+          .into_iter()
+          .map(|mode| u64::from(time_ms[mode as usize]) * u64::from(self.current_na(mode)))
+          .sum();
+        (charge / total_ms) as u32
+      }
+    };
+    UsageEstimate { time_ms, average_current_na }
+  }
+}
+
+/// Stretch of a [`UsageModel`] handed out to consecutive modes.
+struct Phase<'a> {
+  remaining_ms: u32,
+  time_ms: &'a mut [u32; MODES.len()],
+}
+
+impl Phase<'_> {
+  /// Stay in `mode` until `timeout_s` expires or the phase ends.
+  fn spend(&mut self, mode: ChargeMode, timeout_s: u16) {
+    let spent = match timeout_s {
+      0 => self.remaining_ms,
+      timeout_s => self.remaining_ms.min(u32::from(timeout_s) * 1000),
+    };
+    self.time_ms[mode as usize] += spent;
+    self.remaining_ms -= spent;
+  }
+}
+
+/// Repeating pattern of use for [`PowerProfile::usage`]: a moving finger,
+/// then a resting one, then nothing on the pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsageModel {
+  pub touch_ms: u32,
+  pub hold_ms: u32,
+  pub away_ms: u32,
+}
+
+impl UsageModel {
+  pub const fn new(touch_ms: u32, hold_ms: u32, away_ms: u32) -> Self {
+    Self { touch_ms, hold_ms, away_ms }
+  }
+}
+
+/// Outcome of [`PowerProfile::usage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsageEstimate {
+  time_ms: [u32; MODES.len()],
+  /// Current averaged over one repetition of the usage model, in nA.
+  pub average_current_na: u32,
+}
+
+impl UsageEstimate {
+  /// Time spent in `mode` per repetition of the usage model.
+  pub fn time_ms(&self, mode: ChargeMode) -> u32 {
+    self.time_ms[mode as usize]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::config::test_config as config;
+  use crate::{AlpHardware, Hardware, Pin, Pinout, ReportRate, Timing};
+
+  fn with_lp2_rate(config: Config, lp2: u16) -> Config {
+    let mut timing = Timing::default();
+    timing.report_rate = ReportRate::new(10, 50, 20, 80, lp2);
+    config.with_timing(timing)
+  }
+
+  #[test]
+  fn current_drops_with_each_mode() {
+    let profile = config().power_profile();
+    let currents = MODES.map(|mode| profile.current_na(mode));
+    assert!(currents[0] > currents[2] && currents[2] > currents[3] && currents[3] > currents[4]);
+    assert!(currents[4] > SLEEP_NA as u32);
+  }
+
+  #[test]
+  fn more_cycles_cost_more_in_active() {
+    let small = config().with_pinout(Pinout::new([Pin::RxTx0, Pin::RxTx4], [Pin::Tx8], [Pin::RxTx0], [Pin::Tx8]));
+    let (small, large) = (small.power_profile(), config().power_profile());
+    assert!(small.mode(ChargeMode::Active).busy_us < large.mode(ChargeMode::Active).busy_us);
+    // LP2 only senses the ALP channel
+    assert_eq!(small.current_na(ChargeMode::LowPower2), large.current_na(ChargeMode::LowPower2));
+  }
+
+  #[test]
+  fn auto_prox_bursts_save_current_in_low_power() {
+    let config = config();
+    let without = config.with_hardware(
+      Hardware::default().with_alp(
+        AlpHardware::default()
+          .with_lp1_auto_prox_cycles(AutoProxCycles::Disabled)
+          .with_lp2_auto_prox_cycles(AutoProxCycles::Disabled),
+      ),
+    );
+    let lp2 = |config: Config| config.power_profile().current_na(ChargeMode::LowPower2);
+    assert!(lp2(config) < lp2(without));
+  }
+
+  #[test]
+  fn first_touch_latency_follows_the_lp2_rate() {
+    let fast = with_lp2_rate(config(), 160).power_profile();
+    let slow = with_lp2_rate(config(), 320).power_profile();
+    assert_eq!(slow.first_touch_latency_us - fast.first_touch_latency_us, 160_000);
+    assert!(fast.first_touch_latency_us > 170_000);
+  }
+
+  #[test]
+  fn usage_steps_down_on_the_timeouts() {
+    // Timeouts: Active 10 s, IdleTouch 60 s, Idle 10 s, LP1 10 s
+    let profile = config().power_profile();
+    let usage = profile.usage(&UsageModel::new(5_000, 80_000, 60_000));
+    assert_eq!(usage.time_ms(ChargeMode::Active), 5_000 + 10_000 + 10_000);
+    assert_eq!(usage.time_ms(ChargeMode::IdleTouch), 60_000);
+    assert_eq!(usage.time_ms(ChargeMode::Idle), 10_000 + 10_000);
+    assert_eq!(usage.time_ms(ChargeMode::LowPower1), 10_000);
+    assert_eq!(usage.time_ms(ChargeMode::LowPower2), 30_000);
+
+    let idle = profile.usage(&UsageModel::new(0, 0, 3_600_000));
+    assert!(idle.average_current_na < usage.average_current_na);
+    assert!(idle.average_current_na > profile.current_na(ChargeMode::LowPower2));
+  }
+}
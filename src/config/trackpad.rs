@@ -1,11 +1,11 @@
 #[derive(Debug, Clone, Copy)]
 #[packbits::pack(bytes = 18)]
 pub struct Trackpad {
-  #[bits(2)]
+  #[bits(3)]
   pub axes: Axes,
   #[bits(3)]
   pub filters: Filters,
-  #[skip(3)]
+  #[skip(2)]
   pub(crate) total_rx: u8,
   pub(crate) total_tx: u8,
   #[bits(2)]
@@ -271,3 +271,17 @@ impl Default for AxesInset {
     Self::default()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn settings_0_matches_vendor_reference() {
+    // TRACKPAD_SETTINGS_0_0 of the Azoteq example: swap XY, dynamic IIR and MAV.
+    let trackpad =
+      Trackpad::new().with_axes(Axes::new(false, false, true), Resolution::default(), AxesInset::default());
+    let bytes: [u8; 18] = trackpad.try_into().unwrap();
+    assert_eq!(bytes[0], 0x2C);
+  }
+}
@@ -0,0 +1,285 @@
+use crate::frame::MAX_TRACKPAD_CHANNELS;
+use crate::{Config, InterruptMode, MaxCount, Pins};
+
+use super::{MAX_CYCLES, MAX_PINS};
+
+/// Longest retune retry delay and LTA sampling interval, in seconds.
+const MAX_INTERVAL_S: u32 = 60;
+/// Largest conversion period, see [`crate::Frequency::period`].
+const MAX_CONVERSION_PERIOD: u32 = 127;
+/// Largest main oscillator trim, see [`crate::OtherSettings`].
+const MAX_OSC_ADJUST: u32 = 15;
+/// Largest swipe angle below 45°: at 64 · tan θ = 64 a diagonal swipe counts
+/// for both axes.
+const MAX_SWIPE_ANGLE: u32 = 63;
+/// Most issues a configuration can raise, one per check.
+const MAX_ISSUES: usize = 20;
+
+impl Config {
+  /// Check the configuration against the datasheet constraints that the
+  /// register encoding alone does not enforce.
+  ///
+  /// Errors describe settings the device cannot run with, such as a pinout
+  /// needing more than 21 cycles; [`Iqs7211e::initialize`](crate::Iqs7211e::initialize)
+  /// refuses them. Warnings point at settings that are accepted but probably
+  /// not intended, such as a palm threshold above the channel count.
+  pub fn validate(&self) -> ConfigIssues {
+    let mut issues = ConfigIssues::new();
+    let timing = self.timing;
+    issues.range("timing.retune_retry_delay", timing.retune_retry_delay, 0, MAX_INTERVAL_S);
+    issues.range(
+      "timing.long_term_average_reference_sampling_interval",
+      timing.long_term_average_reference_sampling_interval,
+      0,
+      MAX_INTERVAL_S,
+    );
+    let frequency = self.conversion_frequency;
+    issues.range("conversion_frequency.trackpad.period", frequency.trackpad.period, 0, MAX_CONVERSION_PERIOD);
+    issues.range("conversion_frequency.alp.period", frequency.alp.period, 0, MAX_CONVERSION_PERIOD);
+    issues.range("other_settings.main_osc_adjust", self.other_settings.main_osc_adjust, 0, MAX_OSC_ADJUST);
+    issues.range("auto_tune.tune.target", self.auto_tune.tune.target, 0, max_count(self.hardware.trackpad.max_count));
+    issues.range(
+      "auto_tune.alp_tune.target",
+      self.auto_tune.alp_tune.target,
+      0,
+      max_count(self.hardware.alp.max_count),
+    );
+    if self.gestures.swipe.angle > MAX_SWIPE_ANGLE as u8 {
+      let allowed = Some(AllowedRange::new(0, MAX_SWIPE_ANGLE));
+      issues.push(ConfigIssue::warning("gestures.swipe.angle", self.gestures.swipe.angle.into(), allowed));
+    }
+    if matches!(self.interrupt_mode, InterruptMode::Event) {
+      let triggers = self.event_triggers;
+      if !(triggers.gesture || triggers.trackpad || triggers.retuning || triggers.alp || triggers.trackpad_touch) {
+        issues.push(ConfigIssue::warning("event_triggers", 0, None));
+      }
+    }
+
+    let pinout = self.pinout;
+    let (rx, tx) = (pinout.rx, pinout.tx);
+    if rx.len + tx.len > MAX_PINS || pinout.alp_rx.len > MAX_PINS || pinout.alp_tx.len > MAX_PINS {
+      // The pin lists cannot be inspected any further
+      let pins = rx.len + tx.len;
+      issues.push(ConfigIssue::error("pinout", pins as u32, Some(AllowedRange::new(0, MAX_PINS as u32))));
+      return issues;
+    }
+    if let Some(&pin) = rx.pins().iter().find(|pin| !pin.is_rx()) {
+      issues.push(ConfigIssue::error("pinout.rx", pin as u32, Some(AllowedRange::new(0, 7))));
+    }
+    if let Some(&pin) = tx.pins().iter().find(|&&pin| rx.contains(pin)) {
+      issues.push(ConfigIssue::error("pinout.tx", pin as u32, None));
+    }
+    let channels = (rx.len * tx.len) as u32;
+    issues.range("pinout.channels", channels, 0, MAX_TRACKPAD_CHANNELS as u32);
+    if let Some(cycles) = pinout.required_cycles() {
+      issues.range("pinout.cycles", cycles as u32, 0, MAX_CYCLES as u32);
+    }
+    issues.subset("pinout.alp_rx", pinout.alp_rx, rx);
+    issues.subset("pinout.alp_tx", pinout.alp_tx, tx);
+
+    // Kept in step by the builders, but the fields are public
+    issues.matches("trackpad.total_rx", self.trackpad.total_rx, rx.len as u32);
+    issues.matches("trackpad.total_tx", self.trackpad.total_tx, tx.len as u32);
+    issues.matches("alp.rx", self.alp.rx, pinout.alp_rx().into());
+    issues.matches("alp.tx", self.alp.tx, pinout.alp_tx().into());
+
+    if self.gestures.enable.palm && u32::from(self.gestures.palm_threshold) > channels {
+      let threshold = self.gestures.palm_threshold.into();
+      issues.push(ConfigIssue::warning("gestures.palm_threshold", threshold, Some(AllowedRange::new(1, channels))));
+    }
+    issues
+  }
+}
+
+/// Largest count a conversion can reach, which an ATI target must stay below.
+fn max_count(max_count: MaxCount) -> u32 {
+  match max_count {
+    MaxCount::Count1023 => 1023,
+    MaxCount::Count2047 => 2047,
+    MaxCount::Count4095 => 4095,
+    MaxCount::Count16384 => 16384,
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+  /// The device cannot run with the setting.
+  Error,
+  /// The setting is accepted but probably not intended.
+  Warning,
+}
+
+/// Inclusive range of values allowed for a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllowedRange {
+  pub min: u32,
+  pub max: u32,
+}
+
+impl AllowedRange {
+  pub const fn new(min: u32, max: u32) -> Self {
+    Self { min, max }
+  }
+}
+
+/// Setting found by [`Config::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigIssue {
+  pub severity: Severity,
+  /// Path of the field within [`Config`], e.g. `"timing.retune_retry_delay"`.
+  /// `pinout.channels` and `pinout.cycles` name what the pin lists add up to.
+  pub field: &'static str,
+  /// Offending value; the pin number for pin lists.
+  pub value: u32,
+  /// Values accepted, if the constraint is a range.
+  pub allowed: Option<AllowedRange>,
+}
+
+impl ConfigIssue {
+  const fn error(field: &'static str, value: u32, allowed: Option<AllowedRange>) -> Self {
+    Self { severity: Severity::Error, field, value, allowed }
+  }
+
+  const fn warning(field: &'static str, value: u32, allowed: Option<AllowedRange>) -> Self {
+    Self { severity: Severity::Warning, field, value, allowed }
+  }
+
+  pub fn is_error(&self) -> bool {
+    self.severity == Severity::Error
+  }
+}
+
+/// Issues found by [`Config::validate`], in check order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigIssues {
+  issues: [Option<ConfigIssue>; MAX_ISSUES],
+  len: usize,
+}
+
+impl ConfigIssues {
+  const fn new() -> Self {
+    Self { issues: [None; MAX_ISSUES], len: 0 }
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = &ConfigIssue> + '_ {
+    self.issues[..self.len].iter().flatten()
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// First issue the device cannot run with.
+  pub fn first_error(&self) -> Option<ConfigIssue> {
+    self.iter().find(|issue| issue.is_error()).copied()
+  }
+
+  pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> + '_ {
+    self.iter().filter(|issue| issue.is_error())
+  }
+
+  pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> + '_ {
+    self.iter().filter(|issue| !issue.is_error())
+  }
+
+  fn push(&mut self, issue: ConfigIssue) {
+    // Every check raises at most one issue, so this never runs out of room
+    if let Some(slot) = self.issues.get_mut(self.len) {
+      *slot = Some(issue);
+      self.len += 1;
+    }
+  }
+
+  fn range(&mut self, field: &'static str, value: impl Into<u32>, min: u32, max: u32) {
+    let value = value.into();
+    if !(min..=max).contains(&value) {
+      self.push(ConfigIssue::error(field, value, Some(AllowedRange::new(min, max))));
+    }
+  }
+
+  fn matches(&mut self, field: &'static str, value: impl Into<u32>, expected: u32) {
+    self.range(field, value, expected, expected);
+  }
+
+  fn subset(&mut self, field: &'static str, pins: Pins, of: Pins) {
+    if let Some(&pin) = pins.pins().iter().find(|&&pin| !of.contains(pin)) {
+      self.push(ConfigIssue::error(field, pin as u32, None));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::config::test_config as config;
+  use crate::{Gestures, Pin, Pinout, SwipeConfig, Timing};
+
+  #[test]
+  fn defaults_are_valid() {
+    assert!(Config::default().validate().is_empty());
+    assert!(config().validate().is_empty());
+  }
+
+  #[test]
+  fn out_of_range_fields_are_reported_with_their_limits() {
+    let mut timing = Timing::default();
+    timing.retune_retry_delay = 61;
+    let mut config = config().with_timing(timing);
+    config.conversion_frequency.alp.period = 200;
+
+    let issues = config.validate();
+    assert_eq!(issues.len(), 2);
+    assert_eq!(
+      issues.first_error(),
+      Some(ConfigIssue::error("timing.retune_retry_delay", 61, Some(AllowedRange::new(0, 60))))
+    );
+    assert!(issues
+      .errors()
+      .any(|issue| issue.field == "conversion_frequency.alp.period" && issue.value == 200));
+  }
+
+  #[test]
+  fn pinouts_beyond_the_channel_and_cycle_limits_are_errors() {
+    // 7 Rx in prox block A and B need 4 cycles per Tx: 24 cycles for 42 channels
+    let rx = [
+      Pin::RxTx0,
+      Pin::RxTx1,
+      Pin::RxTx2,
+      Pin::RxTx3,
+      Pin::RxTx4,
+      Pin::RxTx5,
+      Pin::RxTx6,
+    ];
+    let tx = [Pin::RxTx7, Pin::Tx8, Pin::Tx9, Pin::Tx10, Pin::Tx11, Pin::Tx12];
+    let issues = Config::default().with_pinout(Pinout::new(rx, tx, [], [])).validate();
+    assert_eq!(issues.len(), 1);
+    let cycles = issues.first_error().unwrap();
+    assert_eq!((cycles.field, cycles.value), ("pinout.cycles", 24));
+
+    let mut config = config();
+    config.pinout.tx = Pinout::new([Pin::RxTx0], [Pin::RxTx0], [], []).tx;
+    config.pinout.alp_tx = Pinout::new([], [Pin::Tx12], [], []).tx;
+    let fields: [&str; 4] = core::array::from_fn(|i| config.validate().iter().nth(i).map_or("", |issue| issue.field));
+    assert_eq!(fields, ["pinout.tx", "pinout.alp_tx", "trackpad.total_tx", "alp.tx"]);
+  }
+
+  #[test]
+  fn questionable_settings_are_warnings() {
+    let gestures = Gestures::default().enable_palm().use_palm_threshold(20);
+    let mut config = config().with_gestures(gestures);
+    config.gestures.swipe = SwipeConfig::default().with_angle(64);
+
+    let issues = config.validate();
+    assert_eq!(issues.first_error(), None);
+    assert_eq!(issues.warnings().count(), 2);
+    let palm = issues
+      .warnings()
+      .find(|issue| issue.field == "gestures.palm_threshold")
+      .unwrap();
+    assert_eq!(palm.allowed, Some(AllowedRange::new(1, 12)));
+  }
+}
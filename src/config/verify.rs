@@ -0,0 +1,162 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use super::auto_tune::ALP_COMPENSATION_LEN;
+use super::SystemSettings;
+use crate::{Config, ConfigSettings, Error, InterruptMode, Iqs7211e, Reg, Suspend};
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Read every configuration window back and compare it with `config`.
+  ///
+  /// Covers the blocks pushed by [`Iqs7211e::initialize`] except the ALP
+  /// compensation values, which the device updates during ATI, and the
+  /// ConfigSettings bits the driver switches while it runs. The first
+  /// differing register is reported as [`Error::ConfigMismatch`]. The first
+  /// read uses the open communication window, if any.
+  pub async fn verify_config(&mut self, config: &Config) -> Result<(), Error<E>> {
+    let encode = |_| Error::Encode;
+    let auto_tune: [u8; 18] = config.auto_tune.try_into().map_err(encode)?;
+    let timing: [u8; 22] = config.timing.try_into().map_err(encode)?;
+    let other_settings: [u8; 2] = config.other_settings.try_into().map_err(encode)?;
+    let alp: [u8; 4] = config.alp.try_into().map_err(encode)?;
+    let channel_output: [u8; 10] = config.channel_output.try_into().map_err(encode)?;
+    let conversion_frequency: [u8; 4] = config.conversion_frequency.try_into().map_err(encode)?;
+    let hardware: [u8; 4] = config.hardware.try_into().map_err(encode)?;
+    let trackpad: [u8; 18] = config.trackpad.try_into().map_err(encode)?;
+    let gestures: [u8; 22] = config.gestures.try_into().map_err(encode)?;
+    let version = config.settings_stamp().ok_or(Error::Encode)?.to_le_bytes();
+    let cycles = config.pinout.cycles();
+
+    self
+      .verify_window(ConfigBlock::AutoTune, Reg::TpAutoTuningMultipliers, &auto_tune[ALP_COMPENSATION_LEN..])
+      .await?;
+    self
+      .verify_window(ConfigBlock::Timing, Reg::ActiveModeReportRate, &timing)
+      .await?;
+    self.verify_config_settings(config).await?;
+    self
+      .verify_window(ConfigBlock::OtherSettings, Reg::OtherSettings, &other_settings)
+      .await?;
+    self.verify_window(ConfigBlock::Alp, Reg::AlpSetup, &alp).await?;
+    self
+      .verify_window(ConfigBlock::ChannelOutput, Reg::TouchSetClearMultipliers, &channel_output)
+      .await?;
+    self
+      .verify_window(ConfigBlock::ConversionFrequency, Reg::TpConvFreq, &conversion_frequency)
+      .await?;
+    self
+      .verify_window(ConfigBlock::Hardware, Reg::TpHardware, &hardware)
+      .await?;
+    self
+      .verify_window(ConfigBlock::Trackpad, Reg::TpRxSettings, &trackpad)
+      .await?;
+    self
+      .verify_window(ConfigBlock::SettingsVersion, Reg::SettingsVersion, &version)
+      .await?;
+    self
+      .verify_window(ConfigBlock::Gestures, Reg::GestureEnable, &gestures)
+      .await?;
+    self
+      .verify_window(ConfigBlock::Mapping, Reg::RxTxMapping0_1, &config.pinout.mapping())
+      .await?;
+    self
+      .verify_window(ConfigBlock::Cycles, Reg::ProxACycle0, &cycles[..63])
+      .await
+  }
+
+  /// Compare ConfigSettings with the staged block, ignoring the delivery mode,
+  /// manual control and end-comms bits the driver switches while it runs, and
+  /// the comms request the device clears on its own. While suspended to wake
+  /// on proximity the event triggers are the driver's as well.
+  async fn verify_config_settings(&mut self, config: &Config) -> Result<(), Error<E>> {
+    let waking = self.suspended() == Some(Suspend::WakeOnProximity);
+    let stable = |mut settings: ConfigSettings| {
+      if waking {
+        settings.event_triggers = config.event_triggers;
+      }
+      let settings = ConfigSettings {
+        interrupt_mode: InterruptMode::Stream,
+        manual_control: false,
+        end_comms: false,
+        comms_request: false,
+        ..settings
+      };
+      <[u8; 2]>::try_from(settings)
+        .map(u16::from_le_bytes)
+        .map_err(|_| Error::Encode)
+    };
+    let expected = stable(SystemSettings::new(config).config_settings)?;
+    let actual = stable(self.config_settings().await?)?;
+
+    if expected == actual {
+      return Ok(());
+    }
+    Err(Error::ConfigMismatch(ConfigMismatch {
+      block: ConfigBlock::ConfigSettings,
+      address: Reg::ConfigSettings as u8,
+      expected,
+      actual,
+    }))
+  }
+
+  async fn verify_window(&mut self, block: ConfigBlock, reg: Reg, expected: &[u8]) -> Result<(), Error<E>> {
+    let mut buf = [0u8; 64];
+    let actual = &mut buf[..expected.len()];
+    self.read_bytes(reg, actual).await?;
+
+    match expected.iter().zip(actual.iter()).position(|(e, a)| e != a) {
+      None => Ok(()),
+      Some(offset) => {
+        let word = offset & !1;
+        let pair = |bytes: &[u8]| u16::from_le_bytes([bytes[word], bytes.get(word + 1).copied().unwrap_or(0)]);
+        Err(Error::ConfigMismatch(ConfigMismatch {
+          block,
+          address: reg as u8 + (offset / 2) as u8,
+          expected: pair(expected),
+          actual: pair(actual),
+        }))
+      }
+    }
+  }
+}
+
+/// Configuration block a register belongs to, named after the [`Config`]
+/// fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigBlock {
+  AutoTune,
+  Timing,
+  /// Event triggers, watchdog and re-ATI enables (ConfigSettings, 0x34). The
+  /// interrupt mode, manual control and end-comms bits are not verified, since
+  /// the driver switches them while it runs.
+  ConfigSettings,
+  OtherSettings,
+  Alp,
+  ChannelOutput,
+  ConversionFrequency,
+  Hardware,
+  Trackpad,
+  /// Stamp derived from [`Config::settings_version`].
+  SettingsVersion,
+  Gestures,
+  /// Rx/Tx mapping derived from [`Config::pinout`].
+  Mapping,
+  /// Cycle allocation derived from [`Config::pinout`].
+  Cycles,
+}
+
+/// Register whose read-back value differs from the staged configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigMismatch {
+  pub block: ConfigBlock,
+  /// 8-bit register address, as listed in the datasheet memory map.
+  pub address: u8,
+  pub expected: u16,
+  pub actual: u16,
+}
@@ -0,0 +1,73 @@
+use crate::{Config, Reg};
+
+/// FNV-1a parameters used to hash the encoded configuration.
+const FNV_OFFSET: u32 = 0x811C_9DC5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+/// Stamp written to the settings version register (0x4A).
+///
+/// The low byte is the minor and the high byte the major version, as in the
+/// `MINOR_VERSION` / `MAJOR_VERSION` defines of the GUI header. A non-zero
+/// stamp lets [`Iqs7211e::initialize`](crate::Iqs7211e::initialize) recognise
+/// a device that kept its settings across a host reset and skip the push.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsVersion {
+  /// Fixed stamp chosen by the application. Zero marks the settings as
+  /// unversioned, so they are always pushed.
+  Fixed(u16),
+  /// Hash of every other byte [`Iqs7211e::initialize`](crate::Iqs7211e::initialize)
+  /// writes, so any change to the configuration changes the stamp. Never zero.
+  Hash,
+}
+
+impl SettingsVersion {
+  /// Stamp for a settings image whose version bytes are still zero.
+  pub(super) fn resolve(self, image: &[u8]) -> u16 {
+    match self {
+      Self::Fixed(version) => version,
+      Self::Hash => {
+        let hash = image
+          .iter()
+          .fold(FNV_OFFSET, |hash, &byte| (hash ^ u32::from(byte)).wrapping_mul(FNV_PRIME));
+        match (hash >> 16) as u16 ^ hash as u16 {
+          0 => 1,
+          folded => folded,
+        }
+      }
+    }
+  }
+}
+
+impl Default for SettingsVersion {
+  fn default() -> Self {
+    Self::Fixed(0)
+  }
+}
+
+impl Config {
+  /// Value the settings version register holds once this configuration is
+  /// pushed, or `None` if a block does not encode.
+  pub fn settings_stamp(&self) -> Option<u16> {
+    let image = super::header::image(self)?;
+    let at = super::header::byte(Reg::SettingsVersion);
+    Some(u16::from_le_bytes([image[at], image[at + 1]]))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::InterruptMode;
+
+  #[test]
+  fn hashed_stamp_follows_the_settings() {
+    let config = Config::default().with_settings_version(SettingsVersion::Hash);
+    let stamp = config.settings_stamp().unwrap();
+    assert_ne!(stamp, 0);
+    assert_eq!(config.settings_stamp(), Some(stamp));
+
+    let streaming = config.with_interrupt_mode(InterruptMode::Stream);
+    assert_ne!(streaming.settings_stamp(), Some(stamp));
+    assert_eq!(Config::default().settings_stamp(), Some(0));
+  }
+}
@@ -0,0 +1,103 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::{Error, Info, Iqs7211e};
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Seed the trackpad references with the latest counts and report how the
+  /// trackpad looks afterwards.
+  pub async fn reseed_trackpad(&mut self) -> Result<Calibration, Error<E>> {
+    self.calibrate(Recalibration::Reseed, true, false).await
+  }
+
+  /// Seed the ALP long-term average with the latest counts and report how the
+  /// ALP looks afterwards.
+  pub async fn reseed_alp(&mut self) -> Result<Calibration, Error<E>> {
+    self.calibrate(Recalibration::Reseed, false, true).await
+  }
+
+  /// Refresh the trackpad and ALP baselines, waiting for the device to finish.
+  ///
+  /// A retune fails with [`Error::AutoTuneTimeout`] if ATI does not complete
+  /// within [`crate::Deadlines::max_polls`] windows.
+  pub async fn recalibrate(&mut self, kind: Recalibration) -> Result<Calibration, Error<E>> {
+    self.calibrate(kind, true, true).await
+  }
+
+  async fn calibrate(&mut self, kind: Recalibration, trackpad: bool, alp: bool) -> Result<Calibration, Error<E>> {
+    self.open_comm_window().await?;
+    self
+      .modify_sys_control(|sys| match kind {
+        Recalibration::Reseed => {
+          sys.trackpad_reseed = trackpad;
+          sys.alp_reseed = alp;
+        }
+        Recalibration::Retune => {
+          sys.trackpad_retune = trackpad;
+          sys.alp_retune = alp;
+        }
+      })
+      .await?;
+
+    // A reseed is applied on the next cycle; ATI takes several. Windows are
+    // forced so this also works in Event mode without a retune trigger.
+    // Each completion flag is only raised for one cycle, and the engines
+    // finish on different ones, so remember which have been seen.
+    let (mut trackpad_pending, mut alp_pending) = (trackpad, alp);
+    for _ in 0..self.deadlines.max_polls {
+      self.open_comm_window().await?;
+      let info = self.info().await?;
+      trackpad_pending &= !info.re_auto_tuning_occurred;
+      alp_pending &= !info.alp_re_auto_tuning_occurred;
+      let done = match kind {
+        Recalibration::Reseed => true,
+        Recalibration::Retune => !trackpad_pending && !alp_pending,
+      };
+      if done {
+        return Ok(Calibration::new(kind, info, trackpad, alp));
+      }
+    }
+    Err(Error::AutoTuneTimeout)
+  }
+}
+
+/// How [`Iqs7211e::recalibrate`] refreshes the baselines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recalibration {
+  /// Take the latest counts as the new references, keeping the ATI results.
+  /// Suits a changed environment, e.g. a closed lid or water wiped off.
+  Reseed,
+  /// Rerun ATI, which also reseeds. Suits a changed sensor or overlay.
+  Retune,
+}
+
+/// Outcome of a reseed or retune.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Calibration {
+  /// The new baselines are in place and nothing is detected on them.
+  Settled,
+  /// The trackpad still reports contacts or the ALP proximity, so something
+  /// was probably on the sensor. Repeat once it is clear.
+  Disturbed(Info),
+  /// ATI flagged an error; the counts could not reach their targets.
+  AutoTuneError(Info),
+}
+
+impl Calibration {
+  fn new(kind: Recalibration, info: Info, trackpad: bool, alp: bool) -> Self {
+    let tune_error = (trackpad && info.auto_tuning_error) || (alp && info.alp_auto_tuning_error);
+    if kind == Recalibration::Retune && tune_error {
+      Self::AutoTuneError(info)
+    } else if (trackpad && info.num_fingers > 0) || (alp && info.alp_output) {
+      Self::Disturbed(info)
+    } else {
+      Self::Settled
+    }
+  }
+}
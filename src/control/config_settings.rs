@@ -1,16 +1,23 @@
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 use embedded_hal_async::i2c::{I2c, SevenBitAddress};
 
-use crate::{Reg, Error, Iqs7211e};
+use crate::rw::Comms;
+use crate::{Error, Iqs7211e, Reg};
 
-impl<I, E, RDY> Iqs7211e<I, RDY>
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
 where
   I: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
+  D: DelayNs,
 {
-  /// Update the interrupt delivery mode (Event or Stream).
+  /// Update the interrupt delivery mode (Event or Stream), also staged for
+  /// later pushes.
   pub async fn set_interrupt_mode(&mut self, mode: InterruptMode) -> Result<(), Error<E>> {
-    self.modify_config_settings(|cfg| cfg.interrupt_mode = mode).await
+    self.open_comm_window().await?;
+    self.modify_config_settings(|cfg| cfg.interrupt_mode = mode).await?;
+    self.config.interrupt_mode = mode;
+    Ok(())
   }
 
   /// Toggle the manual control bit.
@@ -18,7 +25,35 @@ where
     self.modify_config_settings(|cfg| cfg.manual_control = enable).await
   }
 
-  async fn modify_config_settings<F: FnOnce(&mut ConfigSettings)>(&mut self, f: F) -> Result<(), Error<E>> {
+  /// Choose which events open a communication window in Event mode, also
+  /// staged for later pushes.
+  pub async fn set_event_triggers(&mut self, triggers: EventTriggers) -> Result<(), Error<E>> {
+    self.open_comm_window().await?;
+    self.modify_config_settings(|cfg| cfg.event_triggers = triggers).await?;
+    self.config.event_triggers = triggers;
+    Ok(())
+  }
+
+  /// Enable or disable the I²C watchdog, also staged for later pushes.
+  pub async fn set_watchdog(&mut self, enable: bool) -> Result<(), Error<E>> {
+    self.open_comm_window().await?;
+    self.modify_config_settings(|cfg| cfg.watchdog = enable).await?;
+    self.config.watchdog = enable;
+    Ok(())
+  }
+
+  /// Have every communication window end on a command instead of the STOP
+  /// condition, also staged for later pushes. The driver sends the command
+  /// itself, see [`crate::Config::end_comms`].
+  pub async fn set_end_comms(&mut self, enable: bool) -> Result<(), Error<E>> {
+    self.open_comm_window().await?;
+    self.modify_config_settings(|cfg| cfg.end_comms = enable).await?;
+    self.config.end_comms = enable;
+    self.comms = Comms::new(enable);
+    Ok(())
+  }
+
+  pub(crate) async fn modify_config_settings<F: FnOnce(&mut ConfigSettings)>(&mut self, f: F) -> Result<(), Error<E>> {
     let mut settings = self.read(Reg::ConfigSettings).await?;
     f(&mut settings);
     self.write(Reg::ConfigSettings, settings).await
@@ -57,12 +92,13 @@ impl Default for ConfigSettings {
       manual_control: false,
 
       interrupt_mode: InterruptMode::Stream,
-      event_triggers: EventTriggers::new(true, true, false, false, false),
+      event_triggers: EventTriggers::default(),
     }
   }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Events that open a communication window in Event mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[packbits::pack(u8)]
 pub struct EventTriggers {
   pub gesture: bool,
@@ -79,6 +115,13 @@ impl EventTriggers {
   }
 }
 
+impl Default for EventTriggers {
+  /// Gestures and trackpad movement, as set up by the Azoteq reference code.
+  fn default() -> Self {
+    Self::new(true, true, false, false, false)
+  }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum InterruptMode {
   /// I2C is presented each cycle (except auto-prox cycles)
@@ -0,0 +1,146 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::{Error, Iqs7211e, Reg, Version};
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Fetch the ROM library number and revision the application runs on.
+  pub async fn rom_version(&mut self) -> Result<Version, Error<E>> {
+    self.read(Reg::RomVersion).await
+  }
+
+  /// Fetch the application and ROM library versions in one go.
+  pub async fn device_identity(&mut self) -> Result<DeviceIdentity, Error<E>> {
+    // Both blocks in one transfer, which the window lasts for
+    let buf: [u8; 20] = self.read(Reg::AppVersion).await?;
+    let version = |bytes: &[u8]| {
+      <[u8; 10]>::try_from(bytes)
+        .ok()
+        .and_then(|bytes| Version::try_from(bytes).ok())
+        .ok_or(Error::BufferOverflow)
+    };
+    let (app, rom) = (version(&buf[..10])?, version(&buf[10..])?);
+    Ok(DeviceIdentity { product: app.number, app, rom })
+  }
+
+  /// Identity read by the last [`Iqs7211e::initialize`], if any.
+  pub fn identity(&self) -> Option<&DeviceIdentity> {
+    self.identity.as_ref()
+  }
+
+  /// Whether the identity read by the last [`Iqs7211e::initialize`] lies in
+  /// the range accepted by the [`FirmwarePolicy`]. Only `false` when the
+  /// policy merely warns about the mismatch; rejected firmware fails
+  /// initialization instead.
+  pub fn firmware_supported(&self) -> bool {
+    self.identity.map_or(true, |identity| self.firmware.supports(&identity))
+  }
+}
+
+/// Product and firmware revisions reported by the version registers
+/// (0x00–0x09).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceIdentity {
+  pub product: u16,
+  /// Application firmware, the part updated between silicon lots.
+  pub app: Version,
+  /// ROM library the application is built on.
+  pub rom: Version,
+}
+
+/// Major and minor number of a firmware, ordered by release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Release {
+  pub major: u8,
+  pub minor: u8,
+}
+
+impl Release {
+  pub const fn new(major: u8, minor: u8) -> Self {
+    Self { major, minor }
+  }
+}
+
+impl From<Version> for Release {
+  fn from(version: Version) -> Self {
+    Self::new(version.major, version.minor)
+  }
+}
+
+/// Inclusive range of firmware releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReleaseRange {
+  pub min: Release,
+  pub max: Release,
+}
+
+impl ReleaseRange {
+  /// Every release.
+  pub const ANY: Self = Self::new(Release::new(0, 0), Release::new(u8::MAX, u8::MAX));
+
+  pub const fn new(min: Release, max: Release) -> Self {
+    Self { min, max }
+  }
+
+  pub fn contains(&self, version: Version) -> bool {
+    (self.min..=self.max).contains(&Release::from(version))
+  }
+}
+
+/// What [`Iqs7211e::initialize`] does with firmware outside the
+/// [`FirmwarePolicy`] ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirmwareAction {
+  /// Fail with [`Error::UnsupportedFirmware`] before touching the settings.
+  Reject,
+  /// Carry on and report it through [`Iqs7211e::firmware_supported`].
+  Warn,
+}
+
+/// Firmware revisions the application was validated against, checked by
+/// [`Iqs7211e::initialize`] right after the product number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirmwarePolicy {
+  pub app: ReleaseRange,
+  pub rom: ReleaseRange,
+  pub action: FirmwareAction,
+}
+
+impl FirmwarePolicy {
+  /// Accept any firmware.
+  pub const fn any() -> Self {
+    Self { app: ReleaseRange::ANY, rom: ReleaseRange::ANY, action: FirmwareAction::Warn }
+  }
+
+  /// Reject firmware whose application release lies outside `app`.
+  pub const fn new(app: ReleaseRange) -> Self {
+    Self { app, rom: ReleaseRange::ANY, action: FirmwareAction::Reject }
+  }
+
+  pub const fn with_rom(mut self, rom: ReleaseRange) -> Self {
+    self.rom = rom;
+    self
+  }
+
+  pub const fn with_action(mut self, action: FirmwareAction) -> Self {
+    self.action = action;
+    self
+  }
+
+  /// Whether both firmware releases of `identity` lie in range.
+  pub fn supports(&self, identity: &DeviceIdentity) -> bool {
+    self.app.contains(identity.app) && self.rom.contains(identity.rom)
+  }
+}
+
+impl Default for FirmwarePolicy {
+  fn default() -> Self {
+    Self::any()
+  }
+}
@@ -1,18 +1,28 @@
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 use embedded_hal_async::i2c::{I2c, SevenBitAddress};
 
 use crate::{Error, Iqs7211e, Reg};
 
+mod calibration;
 mod config_settings;
+mod firmware;
+mod reset;
+mod suspend;
 mod system_control;
 
+pub use calibration::*;
 pub use config_settings::*;
+pub use firmware::*;
+pub use reset::*;
+pub use suspend::*;
 pub use system_control::*;
 
-impl<I, E, RDY> Iqs7211e<I, RDY>
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
 where
   I: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
+  D: DelayNs,
 {
   /// Fetch the product number and firmware revision as reported by the device.
   pub async fn app_version(&mut self) -> Result<Version, Error<E>> {
@@ -0,0 +1,120 @@
+use core::convert::Infallible;
+
+use embedded_hal::digital::{self, Error as _, OutputPin};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::rw::Comms;
+use crate::{Error, Iqs7211e};
+
+/// Time MCLR is held low, comfortably above the minimum reset pulse.
+const RESET_PULSE_US: u32 = 1_000;
+/// Time the device needs after MCLR is released before it is polled.
+const BOOT_TIME_MS: u32 = 20;
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+  RST: OutputPin,
+{
+  /// Pulse MCLR, wait for the device to boot and push the staged
+  /// configuration again.
+  ///
+  /// Unlike [`Iqs7211e::software_reset`] this works while the device or the
+  /// bus is wedged. Requires a reset line, see [`Iqs7211e::with_reset`], and a
+  /// delay provider for the pulse and boot timings, see
+  /// [`Iqs7211e::with_delay`]; fails with [`Error::NoDelay`] without one.
+  pub async fn hardware_reset(&mut self) -> Result<(), Error<E>> {
+    let reset = self.reset.as_mut().ok_or(Error::NoResetPin)?;
+    if !self.timed {
+      return Err(Error::NoDelay);
+    }
+    reset.set_low().map_err(|e| Error::Pin(e.kind()))?;
+    self.delay.delay_us(RESET_PULSE_US).await;
+
+    let reset = self.reset.as_mut().ok_or(Error::NoResetPin)?;
+    reset.set_high().map_err(|e| Error::Pin(e.kind()))?;
+    self.delay.delay_ms(BOOT_TIME_MS).await;
+    self.comms = Comms::default();
+
+    self.poll_info(|info| info.show_reset).await?.ok_or(Error::RdyTimeout)?;
+    self.configure().await
+  }
+
+  /// Bring an unresponsive device back into operation.
+  ///
+  /// Escalates from forcing a communication window, to a software reset, to a
+  /// [hardware reset](Iqs7211e::hardware_reset) when a reset line is attached,
+  /// and re-pushes the staged configuration whenever the device went through a
+  /// reset. Returns the step that succeeded, or the error of the last one tried.
+  ///
+  /// With a reset line attached the earlier steps need the deadlines of a
+  /// delay provider to give up on a wedged device, so it fails with
+  /// [`Error::NoDelay`] up front without one.
+  pub async fn recover(&mut self) -> Result<Recovery, Error<E>> {
+    if self.reset.is_some() && !self.timed {
+      return Err(Error::NoDelay);
+    }
+    if let Ok(recovery) = self.reconnect().await {
+      return Ok(recovery);
+    }
+
+    match self.restart().await {
+      Ok(()) => Ok(Recovery::SoftwareReset),
+      Err(e) if self.reset.is_none() => Err(e),
+      Err(_) => self.hardware_reset().await.map(|()| Recovery::HardwareReset),
+    }
+  }
+
+  async fn reconnect(&mut self) -> Result<Recovery, Error<E>> {
+    self.force_comms_request().await?;
+    if !self.info().await?.show_reset {
+      return Ok(Recovery::Resumed);
+    }
+    self.configure().await?;
+    Ok(Recovery::Reconfigured)
+  }
+
+  async fn restart(&mut self) -> Result<(), Error<E>> {
+    self.force_comms_request().await?;
+    self.software_reset().await?;
+    self.poll_info(|info| info.show_reset).await?.ok_or(Error::RdyTimeout)?;
+    self.configure().await
+  }
+}
+
+/// Step of [`Iqs7211e::recover`] that brought the device back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+  /// A forced communication window succeeded and the device kept its state.
+  Resumed,
+  /// A forced communication window succeeded, but the device had reset on its
+  /// own and was reconfigured.
+  Reconfigured,
+  /// The device was reconfigured after a software reset.
+  SoftwareReset,
+  /// The device was reconfigured after a hardware reset.
+  HardwareReset,
+}
+
+/// Placeholder reset line used until one is attached with
+/// [`Iqs7211e::with_reset`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoReset;
+
+impl digital::ErrorType for NoReset {
+  type Error = Infallible;
+}
+
+impl OutputPin for NoReset {
+  fn set_low(&mut self) -> Result<(), Infallible> {
+    Ok(())
+  }
+
+  fn set_high(&mut self) -> Result<(), Infallible> {
+    Ok(())
+  }
+}
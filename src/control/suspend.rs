@@ -0,0 +1,90 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::{ConfigSettings, Error, EventTriggers, InterruptMode, Iqs7211e, Reg};
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Put the device to sleep along with the host.
+  ///
+  /// [`Suspend::Deep`] sets the suspend bit; the device stops once the
+  /// communication window closes and [`Iqs7211e::next_event`] fails with
+  /// [`Error::Suspended`] until [`Iqs7211e::resume`].
+  ///
+  /// [`Suspend::WakeOnProximity`] keeps sensing but switches to Event mode
+  /// with only the ALP trigger enabled, so RDY stays high until a hand
+  /// approaches. [`Iqs7211e::next_event`] waits for that and resumes on its
+  /// own, reporting [`crate::Event::Proximity`] if the event filter enables it.
+  pub async fn suspend(&mut self, mode: Suspend) -> Result<(), Error<E>> {
+    if self.suspended.is_some() {
+      self.resume().await?;
+    }
+
+    self.open_comm_window().await?;
+    let settings: ConfigSettings = self.read(Reg::ConfigSettings).await?;
+    match mode {
+      Suspend::Deep => self.modify_sys_control(|sys| sys.suspend = true).await?,
+      Suspend::WakeOnProximity => {
+        let mut wake = settings;
+        wake.interrupt_mode = InterruptMode::Event;
+        wake.event_triggers = EventTriggers::new(false, false, false, true, false);
+        self.write(Reg::ConfigSettings, wake).await?;
+      }
+    }
+    self.suspended = Some(Suspended { mode, settings });
+    self.events.forget();
+    // Deep suspend starts once this window ends
+    self.end_comm_window().await
+  }
+
+  /// Wake the device after [`Iqs7211e::suspend`].
+  ///
+  /// Forces a communication window, which also wakes a deep suspended device,
+  /// and restores normal operation in it. The device reseeds the trackpad
+  /// references on its own after a deep suspend.
+  pub async fn resume(&mut self) -> Result<(), Error<E>> {
+    let Some(suspended) = self.suspended else {
+      return Ok(());
+    };
+
+    self.force_comms_request().await?;
+    self.wake(suspended).await
+  }
+
+  /// Undo `suspended` inside an open communication window.
+  pub(crate) async fn wake(&mut self, suspended: Suspended) -> Result<(), Error<E>> {
+    match suspended.mode {
+      Suspend::Deep => self.modify_sys_control(|sys| sys.suspend = false).await?,
+      Suspend::WakeOnProximity => self.write(Reg::ConfigSettings, suspended.settings).await?,
+    }
+    self.suspended = None;
+    Ok(())
+  }
+
+  /// How the device is suspended, if it is.
+  pub fn suspended(&self) -> Option<Suspend> {
+    self.suspended.map(|suspended| suspended.mode)
+  }
+}
+
+/// How the device waits while the host sleeps, see [`Iqs7211e::suspend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suspend {
+  /// No sensing at all, below 1.5 µA, until the host resumes the device.
+  Deep,
+  /// Sense in the low-power modes and wake the host on ALP proximity.
+  WakeOnProximity,
+}
+
+/// Suspension tracked by the driver.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Suspended {
+  mode: Suspend,
+  /// Settings to restore on resume.
+  settings: ConfigSettings,
+}
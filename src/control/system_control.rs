@@ -1,12 +1,15 @@
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 use embedded_hal_async::i2c::{I2c, SevenBitAddress};
 
+use crate::rw::Comms;
 use crate::{Error, Iqs7211e, Reg};
 
-impl<I, E, RDY> Iqs7211e<I, RDY>
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
 where
   I: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
+  D: DelayNs,
 {
   /// Set the ACK_RESET bit which clears the SHOW_RESET flag in [`crate::Info`].
   pub async fn ack_reset(&mut self) -> Result<(), Error<E>> {
@@ -25,7 +28,10 @@ where
 
   /// Issue a software reset (SW_RESET bit) to the controller.
   pub async fn software_reset(&mut self) -> Result<(), Error<E>> {
-    self.modify_sys_control(|sys| sys.sw_reset = true).await
+    self.modify_sys_control(|sys| sys.sw_reset = true).await?;
+    // The device boots with windows ending on STOP
+    self.comms = Comms::default();
+    Ok(())
   }
 
   /// Change the charge/sensing mode used by the controller.
@@ -33,7 +39,7 @@ where
     self.modify_sys_control(|sys| sys.charge_mode = mode).await
   }
 
-  async fn modify_sys_control<F: FnOnce(&mut SysControl)>(&mut self, f: F) -> Result<(), Error<E>> {
+  pub(crate) async fn modify_sys_control<F: FnOnce(&mut SysControl)>(&mut self, f: F) -> Result<(), Error<E>> {
     let mut control = self.read(Reg::SysControl).await?;
     f(&mut control);
     self.write(Reg::SysControl, control).await
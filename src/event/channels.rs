@@ -0,0 +1,173 @@
+use core::fmt;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::{Error, Iqs7211e, Reg};
+
+/// Trackpad channels covered by the touch state registers.
+const MAX_CHANNELS: usize = 42;
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Read which trackpad channels are in touch during the current cycle,
+  /// laid out by the configured [`crate::Pinout`].
+  pub async fn channel_touch_map(&mut self) -> Result<ChannelTouchMap, Error<E>> {
+    let states: [u8; 6] = self.read(Reg::TouchState0).await?;
+    let pinout = &self.config.pinout;
+    Ok(ChannelTouchMap::from_registers(states, pinout.rx.len, pinout.tx.len))
+  }
+}
+
+/// Touch state of every trackpad channel (TouchState0..2, 0x18..0x1A).
+///
+/// Channels are addressed by `(rx, tx)`, the positions of the electrodes in
+/// the [`crate::Pinout`] Rx and Tx lists. The device numbers them Tx-major,
+/// channel `tx * rx_count + rx`, in the same order as the flattened arrays of
+/// [`crate::SetupSnapshot`].
+///
+/// The [`fmt::Display`] rendering draws one row per Tx with `#` for channels
+/// in touch, e.g. for logging a stuck electrode:
+///
+/// ```text
+///     0 1 2 3
+/// tx 0 . . . .
+/// tx 1 . # # .
+/// tx 2 . . . .
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelTouchMap {
+  bits: u64,
+  rx: usize,
+  tx: usize,
+}
+
+impl ChannelTouchMap {
+  /// Decode the six bytes read from TouchState0 onwards for a trackpad of
+  /// `rx` by `tx` electrodes. Bits past the trackpad are dropped.
+  pub fn from_registers(states: [u8; 6], rx: usize, tx: usize) -> Self {
+    let mut bytes = [0u8; 8];
+    bytes[..6].copy_from_slice(&states);
+    let channels = (rx * tx).min(MAX_CHANNELS);
+    let bits = u64::from_le_bytes(bytes) & ((1u64 << channels) - 1);
+    Self { bits, rx, tx }
+  }
+
+  pub fn rx_count(&self) -> usize {
+    self.rx
+  }
+
+  pub fn tx_count(&self) -> usize {
+    self.tx
+  }
+
+  /// Channel number of the electrode crossing, if it is on the trackpad.
+  pub fn channel(&self, rx: usize, tx: usize) -> Option<usize> {
+    (rx < self.rx && tx < self.tx).then_some(tx * self.rx + rx)
+  }
+
+  /// Whether the channel at `(rx, tx)` is in touch; `false` off the trackpad.
+  pub fn is_touched(&self, rx: usize, tx: usize) -> bool {
+    self
+      .channel(rx, tx)
+      .is_some_and(|channel| self.bits & (1 << channel) != 0)
+  }
+
+  /// Number of channels in touch.
+  pub fn count(&self) -> usize {
+    self.bits.count_ones() as usize
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.bits == 0
+  }
+
+  /// `(rx, tx)` of every channel in touch, in channel order.
+  pub fn active(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..self.rx * self.tx)
+      .filter(|&channel| self.bits & (1 << channel) != 0)
+      .map(|channel| (channel % self.rx, channel / self.rx))
+  }
+
+  /// Rx positions with at least one channel in touch.
+  pub fn active_rx(&self) -> impl Iterator<Item = usize> + '_ {
+    (0..self.rx).filter(|&rx| (0..self.tx).any(|tx| self.is_touched(rx, tx)))
+  }
+
+  /// Tx positions with at least one channel in touch.
+  pub fn active_tx(&self) -> impl Iterator<Item = usize> + '_ {
+    (0..self.tx).filter(|&tx| (0..self.rx).any(|rx| self.is_touched(rx, tx)))
+  }
+}
+
+impl fmt::Display for ChannelTouchMap {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "   ")?;
+    for rx in 0..self.rx {
+      write!(f, "{rx:>2}")?;
+    }
+    for tx in 0..self.tx {
+      write!(f, "\ntx{tx:>2}")?;
+      for rx in 0..self.rx {
+        let cell = if self.is_touched(rx, tx) { '#' } else { '.' };
+        write!(f, " {cell}")?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use core::fmt::Write;
+
+  struct Text {
+    buf: [u8; 256],
+    len: usize,
+  }
+
+  impl Write for Text {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+      self.buf[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+      self.len += s.len();
+      Ok(())
+    }
+  }
+
+  /// 4 Rx by 3 Tx with channels 5 and 6 (rx 1 and 2 on tx 1) in touch.
+  fn map() -> ChannelTouchMap {
+    ChannelTouchMap::from_registers([0b0110_0000, 0, 0, 0, 0, 0], 4, 3)
+  }
+
+  #[test]
+  fn indexes_channels_by_rx_and_tx() {
+    let map = map();
+    assert!(map.is_touched(1, 1) && map.is_touched(2, 1));
+    assert!(!map.is_touched(1, 0) && !map.is_touched(4, 1));
+    assert_eq!(map.channel(3, 2), Some(11));
+    assert_eq!(map.count(), 2);
+
+    let mut active = map.active();
+    assert_eq!((active.next(), active.next(), active.next()), (Some((1, 1)), Some((2, 1)), None));
+    assert!(map.active_rx().eq([1, 2]));
+    assert!(map.active_tx().eq([1]));
+
+    // Bits of channels past the trackpad are ignored.
+    let stray = ChannelTouchMap::from_registers([0, 0x10, 0, 0, 0, 0x02], 4, 3);
+    assert!(stray.is_empty());
+  }
+
+  #[test]
+  fn renders_ascii_grid() {
+    let mut text = Text { buf: [0; 256], len: 0 };
+    write!(text, "{}", map()).unwrap();
+    let expected = "    0 1 2 3\ntx 0 . . . .\ntx 1 . # # .\ntx 2 . . . .";
+    assert_eq!(core::str::from_utf8(&text.buf[..text.len]).unwrap(), expected);
+  }
+}
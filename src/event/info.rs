@@ -1,12 +1,14 @@
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 use embedded_hal_async::i2c::{I2c, SevenBitAddress};
 
 use crate::{ChargeMode, Error, Iqs7211e, Reg};
 
-impl<I, E, RDY> Iqs7211e<I, RDY>
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
 where
   I: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
+  D: DelayNs,
 {
   pub async fn info(&mut self) -> Result<Info, Error<E>> {
     self.read(Reg::InfoFlags).await
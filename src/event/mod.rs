@@ -1,47 +1,88 @@
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 use embedded_hal_async::i2c::{I2c, SevenBitAddress};
 
-use crate::{Error, Iqs7211e, Reg};
+use crate::{Error, Iqs7211e, Reg, Suspend};
 
+mod channels;
 mod gesture;
 mod info;
+mod motion;
 mod touchpoint;
+mod tracker;
 
+pub use channels::*;
 pub use gesture::*;
 pub use info::*;
+pub use motion::*;
 pub use touchpoint::*;
+pub use tracker::*;
 
-impl<I, E, RDY> Iqs7211e<I, RDY>
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
 where
   I: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
+  D: DelayNs,
 {
   /// Await the next interesting event from the controller.
   ///
-  /// Resolves only when either a gesture is present or the trackpad reports
-  /// movement/contacts.
+  /// Resolves when a gesture is present, the trackpad reports contacts, or one
+  /// of the state changes selected with [`Iqs7211e::with_event_filter`]
+  /// occurs. A report carrying several changes yields them over consecutive
+  /// calls.
+  ///
+  /// An unexpected reset is handled here: the staged configuration is pushed
+  /// again before [`Event::DeviceReset`] is reported.
+  ///
+  /// Fails with [`Error::Suspended`] while the device is in [`Suspend::Deep`].
+  /// In [`Suspend::WakeOnProximity`] it waits for the ALP to detect a hand and
+  /// resumes normal reporting, returning [`Event::Proximity`] if the filter
+  /// enables it.
   pub async fn next_event(&mut self) -> Result<Event, Error<E>> {
     loop {
+      if let Some(event) = self.events.queue.pop() {
+        return Ok(event);
+      }
+      if self.suspended() == Some(Suspend::Deep) {
+        return Err(Error::Suspended);
+      }
+
       // Take a coherent snapshot in a single RDY window
-      self.wait_for_comm_window().await?;
-      let gesture = self.gesture().await.ok();
-      let info: Info = self.read(Reg::InfoFlags).await?;
-      let touchpoints: Touchpoints = self.touchpoints().await?;
+      self.wait_for_event_window().await?;
+      let (motion, gesture, info, touchpoints) = self.report().await?;
 
-      if let Some(gesture) = gesture {
-        return Ok(Event::Gesture(gesture, info));
+      if info.show_reset {
+        self.configure().await?;
+        self.events.forget();
+        if self.events.filter.device_reset {
+          return Ok(Event::DeviceReset);
+        }
+        continue;
       }
 
-      // Classify movement by number of active contacts.
-      if !touchpoints.primary.is_empty() && !touchpoints.secondary.is_empty() {
-        return Ok(Event::MultiTouch(touchpoints.primary, touchpoints.secondary, info));
-      } else if !touchpoints.primary.is_empty() {
-        return Ok(Event::Touch(touchpoints.primary, info));
+      if let (Some(suspended), true) = (self.suspended, info.alp_output) {
+        self.wake(suspended).await?;
       }
+      self.events.decode(info, gesture, touchpoints, motion);
+      // Let the device carry on sensing while the events are handled
+      self.end_comm_window().await?;
 
       // Otherwise, keep waiting for the next RDY window
     }
   }
+
+  /// Movement, gesture, flags and contacts of the current cycle in one
+  /// transfer, since the STOP condition closes the window without the
+  /// end-comms option.
+  async fn report(&mut self) -> Result<(Vector, Option<Gesture>, Info, Touchpoints), Error<E>> {
+    let buf: [u8; 28] = self.read(Reg::RelativeX).await?;
+    let motion = <[u8; 10]>::try_from(&buf[..10]).map_err(|_| Error::BufferOverflow)?;
+    let (motion, gesture) = motion::decode_motion_and_gesture(&motion);
+    let info = Info::try_from([buf[10], buf[11]]).map_err(|_| Error::BufferOverflow)?;
+    let touchpoints = <[u8; 16]>::try_from(&buf[12..]).map_err(|_| Error::BufferOverflow)?;
+    let touchpoints = Touchpoints::try_from(touchpoints).map_err(|_| Error::BufferOverflow)?;
+    Ok((motion, gesture, info, touchpoints))
+  }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -69,4 +110,204 @@ pub enum Event {
   Gesture(Gesture, Info),
   Touch(Touchpoint, Info),
   MultiTouch(Touchpoint, Touchpoint, Info),
+  /// The last finger left the trackpad.
+  Release,
+  /// The primary finger moved by this many trackpad counts, see
+  /// [`MotionAccumulator`].
+  Motion(Vector),
+  /// The ALP channel entered (`true`) or left (`false`) proximity.
+  Proximity(bool),
+  /// A trackpad or ALP re-ATI completed.
+  Retuned,
+  /// A trackpad or ALP ATI routine failed to reach its target.
+  AutoTuneError,
+  /// The device reset on its own and was reconfigured.
+  DeviceReset,
+  /// More fingers touched the trackpad than can be tracked.
+  TooManyFingers,
+}
+
+/// Selects which state changes [`Iqs7211e::next_event`] reports in addition
+/// to gestures and contacts. None of them are reported by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventFilter {
+  pub release: bool,
+  pub motion: bool,
+  pub proximity: bool,
+  pub retuned: bool,
+  pub auto_tune_error: bool,
+  pub device_reset: bool,
+  pub too_many_fingers: bool,
+}
+
+impl EventFilter {
+  /// Report every state change.
+  pub const fn all() -> Self {
+    Self {
+      release: true,
+      motion: true,
+      proximity: true,
+      retuned: true,
+      auto_tune_error: true,
+      device_reset: true,
+      too_many_fingers: true,
+    }
+  }
+
+  /// Report gestures and contacts only.
+  pub const fn none() -> Self {
+    Self {
+      release: false,
+      motion: false,
+      proximity: false,
+      retuned: false,
+      auto_tune_error: false,
+      device_reset: false,
+      too_many_fingers: false,
+    }
+  }
+
+  pub const fn with_release(mut self, enabled: bool) -> Self {
+    self.release = enabled;
+    self
+  }
+
+  pub const fn with_motion(mut self, enabled: bool) -> Self {
+    self.motion = enabled;
+    self
+  }
+
+  pub const fn with_proximity(mut self, enabled: bool) -> Self {
+    self.proximity = enabled;
+    self
+  }
+
+  pub const fn with_retuned(mut self, enabled: bool) -> Self {
+    self.retuned = enabled;
+    self
+  }
+
+  pub const fn with_auto_tune_error(mut self, enabled: bool) -> Self {
+    self.auto_tune_error = enabled;
+    self
+  }
+
+  pub const fn with_device_reset(mut self, enabled: bool) -> Self {
+    self.device_reset = enabled;
+    self
+  }
+
+  pub const fn with_too_many_fingers(mut self, enabled: bool) -> Self {
+    self.too_many_fingers = enabled;
+    self
+  }
+}
+
+impl Default for EventFilter {
+  fn default() -> Self {
+    Self::none()
+  }
+}
+
+/// Most events a single report can produce.
+const MAX_PENDING: usize = 7;
+
+/// Edge detection and buffering behind [`Iqs7211e::next_event`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EventState {
+  pub(crate) filter: EventFilter,
+  pub(crate) queue: EventQueue,
+  touching: bool,
+  proximity: bool,
+  auto_tune_error: bool,
+  too_many_fingers: bool,
+}
+
+impl EventState {
+  pub(crate) const fn new(filter: EventFilter) -> Self {
+    Self {
+      filter,
+      queue: EventQueue::new(),
+      touching: false,
+      proximity: false,
+      auto_tune_error: false,
+      too_many_fingers: false,
+    }
+  }
+
+  /// Drop pending events and tracked state, e.g. after the device reset.
+  pub(crate) fn forget(&mut self) {
+    *self = Self::new(self.filter);
+  }
+
+  /// Queue the events carried by one report.
+  pub(crate) fn decode(&mut self, info: Info, gesture: Option<Gesture>, touchpoints: Touchpoints, motion: Vector) {
+    let filter = self.filter;
+    let auto_tune_error = info.auto_tuning_error || info.alp_auto_tuning_error;
+    let touching = !touchpoints.primary.is_empty();
+
+    if filter.auto_tune_error && auto_tune_error && !self.auto_tune_error {
+      self.queue.push(Event::AutoTuneError);
+    }
+    if filter.retuned && (info.re_auto_tuning_occurred || info.alp_re_auto_tuning_occurred) {
+      self.queue.push(Event::Retuned);
+    }
+    if filter.too_many_fingers && info.too_many_fingers && !self.too_many_fingers {
+      self.queue.push(Event::TooManyFingers);
+    }
+    if filter.proximity && info.alp_output != self.proximity {
+      self.queue.push(Event::Proximity(info.alp_output));
+    }
+
+    if let Some(gesture) = gesture {
+      self.queue.push(Event::Gesture(gesture, info));
+    } else if touching && !touchpoints.secondary.is_empty() {
+      // Classify movement by number of active contacts.
+      self
+        .queue
+        .push(Event::MultiTouch(touchpoints.primary, touchpoints.secondary, info));
+    } else if touching {
+      self.queue.push(Event::Touch(touchpoints.primary, info));
+    }
+    if filter.motion && motion != Vector::new(0, 0) {
+      self.queue.push(Event::Motion(motion));
+    }
+    if filter.release && self.touching && !touching {
+      self.queue.push(Event::Release);
+    }
+
+    self.touching = touching;
+    self.proximity = info.alp_output;
+    self.auto_tune_error = auto_tune_error;
+    self.too_many_fingers = info.too_many_fingers;
+  }
+}
+
+/// Events decoded from one report, handed out one per call.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EventQueue {
+  events: [Option<Event>; MAX_PENDING],
+  read: usize,
+  write: usize,
+}
+
+impl EventQueue {
+  const fn new() -> Self {
+    Self { events: [None; MAX_PENDING], read: 0, write: 0 }
+  }
+
+  fn push(&mut self, event: Event) {
+    self.events[self.write] = Some(event);
+    self.write += 1;
+  }
+
+  pub(crate) fn pop(&mut self) -> Option<Event> {
+    if self.read == self.write {
+      self.read = 0;
+      self.write = 0;
+      return None;
+    }
+    self.read += 1;
+    self.events[self.read - 1].take()
+  }
 }
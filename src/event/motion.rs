@@ -0,0 +1,208 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::{Error, Gesture, Iqs7211e, Reg, Vector};
+
+/// Gains are given in percent.
+const PERCENT: i64 = 100;
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Movement of the primary finger during the current cycle, in trackpad
+  /// counts. Zero while no finger is down or when it just landed.
+  pub async fn relative_motion(&mut self) -> Result<Vector, Error<E>> {
+    self.read(Reg::RelativeX).await
+  }
+
+  /// Relative movement and gesture of the current cycle in one transfer.
+  pub(crate) async fn motion_and_gesture(&mut self) -> Result<(Vector, Option<Gesture>), Error<E>> {
+    let buf: [u8; 10] = self.read(Reg::RelativeX).await?;
+    Ok(decode_motion_and_gesture(&buf))
+  }
+}
+
+/// Relative movement and gesture held in the registers from RelativeX on.
+pub(crate) fn decode_motion_and_gesture(buf: &[u8; 10]) -> (Vector, Option<Gesture>) {
+  let motion = Vector::from([buf[0], buf[1], buf[2], buf[3]]);
+  let gesture = <[u8; 6]>::try_from(&buf[4..])
+    .ok()
+    .and_then(|g| Gesture::try_from(g).ok());
+  (motion, gesture)
+}
+
+impl From<[u8; 4]> for Vector {
+  fn from(data: [u8; 4]) -> Self {
+    Self::new(i16::from_le_bytes([data[0], data[1]]), i16::from_le_bytes([data[2], data[3]]))
+  }
+}
+
+/// Pointer acceleration curve.
+///
+/// Movement up to `threshold` counts per report passes at 100 % gain. Above
+/// it the gain grows by `slope` percent per count, up to `max_gain` percent.
+/// Speed is the length of the movement vector of one report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Acceleration {
+  pub threshold: u16,
+  pub slope: u16,
+  pub max_gain: u16,
+}
+
+impl Acceleration {
+  /// Constant 100 % gain.
+  pub const NONE: Self = Self { threshold: 0, slope: 0, max_gain: 100 };
+
+  pub const fn new(threshold: u16, slope: u16, max_gain: u16) -> Self {
+    Self { threshold, slope, max_gain }
+  }
+
+  /// Gain in percent applied to a movement of `speed` counts.
+  pub fn gain(&self, speed: u16) -> u16 {
+    let over = u32::from(speed.saturating_sub(self.threshold));
+    let gain = 100 + over * u32::from(self.slope);
+    gain.min(u32::from(self.max_gain.max(100))) as u16
+  }
+}
+
+impl Default for Acceleration {
+  fn default() -> Self {
+    Self::NONE
+  }
+}
+
+/// Turns relative trackpad motion into whole cursor steps.
+///
+/// Movement is scaled by the sensitivity and the [`Acceleration`] gain for
+/// its speed. The fractional part left after rounding towards zero is carried
+/// into the next report, so slow movement still adds up instead of being lost.
+///
+/// # Example
+/// ```no_run
+/// # async fn run<I: embedded_hal_async::i2c::I2c, R: embedded_hal_async::digital::Wait>(
+/// #   mut dev: iqs7211e::Iqs7211e<I, R>, move_cursor: impl Fn(i16, i16),
+/// # ) {
+/// use iqs7211e::{Acceleration, Event, MotionAccumulator};
+///
+/// let mut pointer = MotionAccumulator::new()
+///   .with_sensitivity(50)
+///   .with_acceleration(Acceleration::new(8, 10, 300));
+/// loop {
+///   match dev.next_event().await.unwrap() {
+///     Event::Motion(motion) => {
+///       let step = pointer.update(motion);
+///       move_cursor(step.dx, step.dy);
+///     }
+///     Event::Release => pointer.reset(),
+///     _ => {}
+///   }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct MotionAccumulator {
+  sensitivity: u16,
+  acceleration: Acceleration,
+  /// Unreported movement, in hundredths of a cursor step.
+  carry: (i64, i64),
+}
+
+impl MotionAccumulator {
+  /// Pass movement through at 100 % without acceleration.
+  pub const fn new() -> Self {
+    Self { sensitivity: 100, acceleration: Acceleration::NONE, carry: (0, 0) }
+  }
+
+  /// Scale all movement by `percent`, e.g. 50 for half a step per count.
+  pub const fn with_sensitivity(mut self, percent: u16) -> Self {
+    self.sensitivity = percent;
+    self
+  }
+
+  pub const fn with_acceleration(mut self, acceleration: Acceleration) -> Self {
+    self.acceleration = acceleration;
+    self
+  }
+
+  /// Add one report of movement and take the whole cursor steps it completes.
+  pub fn update(&mut self, motion: Vector) -> Vector {
+    let gain = i64::from(self.acceleration.gain(speed(motion)));
+    let scale = |delta: i16, carry: &mut i64| {
+      let total = i64::from(delta) * i64::from(self.sensitivity) * gain / PERCENT + *carry;
+      *carry = total % PERCENT;
+      (total / PERCENT).clamp(i16::MIN.into(), i16::MAX.into()) as i16
+    };
+    let (mut cx, mut cy) = self.carry;
+    let step = Vector::new(scale(motion.dx, &mut cx), scale(motion.dy, &mut cy));
+    self.carry = (cx, cy);
+    step
+  }
+
+  /// Drop the carried fraction, e.g. when the finger lifts.
+  pub fn reset(&mut self) {
+    self.carry = (0, 0);
+  }
+}
+
+impl Default for MotionAccumulator {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Length of `motion`, rounded down.
+fn speed(motion: Vector) -> u16 {
+  let dx = u32::from(motion.dx.unsigned_abs());
+  let dy = u32::from(motion.dy.unsigned_abs());
+  let square = dx * dx + dy * dy;
+  // Integer square root by bisection; no i16 vector is longer than 46341.
+  let (mut low, mut high) = (0u32, 46341);
+  while low < high {
+    let mid = (low + high).div_ceil(2);
+    match mid * mid <= square {
+      true => low = mid,
+      false => high = mid - 1,
+    }
+  }
+  low as u16
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn carries_sub_step_motion() {
+    let mut pointer = MotionAccumulator::new().with_sensitivity(30);
+    let steps = [1, 1, 1, 1].map(|dx| pointer.update(Vector::new(dx, -dx)));
+    // 0.3, 0.6, 0.9 and 1.2 steps accumulated: one step on the fourth report.
+    assert_eq!(steps.map(|s| (s.dx, s.dy)), [(0, 0), (0, 0), (0, 0), (1, -1)]);
+
+    pointer.reset();
+    assert_eq!(pointer.update(Vector::new(2, 0)), Vector::new(0, 0));
+  }
+
+  #[test]
+  fn accelerates_fast_motion() {
+    let curve = Acceleration::new(5, 20, 200);
+    assert_eq!(curve.gain(3), 100);
+    assert_eq!(curve.gain(7), 140);
+    assert_eq!(curve.gain(100), 200);
+
+    let mut pointer = MotionAccumulator::new().with_acceleration(curve);
+    assert_eq!(pointer.update(Vector::new(3, 4)), Vector::new(3, 4));
+    // Length 10: 5 counts over the threshold, 200 % gain.
+    assert_eq!(pointer.update(Vector::new(-6, 8)), Vector::new(-12, 16));
+  }
+
+  #[test]
+  fn measures_speed() {
+    assert_eq!(speed(Vector::new(3, -4)), 5);
+    assert_eq!(speed(Vector::new(0, 0)), 0);
+    assert_eq!(speed(Vector::new(i16::MIN, i16::MIN)), 46340);
+  }
+}
@@ -1,12 +1,14 @@
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 use embedded_hal_async::i2c::{I2c, SevenBitAddress};
 
-use crate::{Reg, Error, Iqs7211e, Point};
+use crate::{Error, Iqs7211e, Point, Reg};
 
-impl<I, E, RDY> Iqs7211e<I, RDY>
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
 where
   I: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
+  D: DelayNs,
 {
   pub async fn touchpoints(&mut self) -> Result<Touchpoints, Error<E>> {
     self.read(Reg::Finger1X).await
@@ -32,6 +34,9 @@ pub struct Touchpoint {
 }
 
 impl Touchpoint {
+  /// Contents of a slot without a finger.
+  pub const EMPTY: Self = Self { point: Point { x: 0xFFFF, y: 0xFFFF }, strength: 0, area: 0 };
+
   pub fn is_empty(&self) -> bool {
     self.point.x == 0xFFFF && self.point.y == 0xFFFF
   }
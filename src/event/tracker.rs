@@ -0,0 +1,338 @@
+use crate::{Event, Point, Touchpoint, Touchpoints};
+
+/// Contacts the IQS7211E reports per cycle.
+const SLOTS: usize = 2;
+/// Most events one frame can produce: every slot lifting and going down again.
+const MAX_CONTACT_EVENTS: usize = 2 * SLOTS;
+
+/// Lifecycle stage of a tracked contact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+  /// The contact appeared in this frame.
+  Down,
+  /// The contact moved, or its strength or area changed.
+  Move,
+  /// The contact is gone; the fields hold its last reported state.
+  Up,
+}
+
+/// Finger followed across frames under a stable identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contact {
+  /// Identifier kept for as long as the finger stays on the pad.
+  pub id: u8,
+  pub point: Point,
+  pub strength: u16,
+  pub area: u16,
+  /// Timestamp of the frame the contact went down in.
+  pub down_at: u64,
+  /// Timestamp of the frame this state was reported in.
+  pub timestamp: u64,
+}
+
+/// Change of one contact between two frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContactEvent {
+  pub phase: Phase,
+  pub contact: Contact,
+}
+
+/// Contact events produced by one frame, `Up` first, then `Move`, then `Down`.
+#[derive(Debug, Clone, Copy)]
+pub struct ContactEvents {
+  events: [Option<ContactEvent>; MAX_CONTACT_EVENTS],
+  next: usize,
+}
+
+impl ContactEvents {
+  const fn new() -> Self {
+    Self { events: [None; MAX_CONTACT_EVENTS], next: 0 }
+  }
+
+  /// Insert `event` after every queued event of the same or an earlier phase.
+  fn push(&mut self, event: ContactEvent) {
+    let rank = |phase: Phase| match phase {
+      Phase::Up => 0,
+      Phase::Move => 1,
+      Phase::Down => 2,
+    };
+    let at = self
+      .events
+      .iter()
+      .position(|e| e.map_or(true, |e| rank(e.phase) > rank(event.phase)))
+      .unwrap_or(MAX_CONTACT_EVENTS - 1);
+    self.events[at..].rotate_right(1);
+    self.events[at] = Some(event);
+  }
+}
+
+impl Iterator for ContactEvents {
+  type Item = ContactEvent;
+
+  fn next(&mut self) -> Option<ContactEvent> {
+    let event = self.events.get(self.next).copied().flatten()?;
+    self.next += 1;
+    Some(event)
+  }
+}
+
+/// Turns the raw finger slots of [`Touchpoints`] into per-contact lifecycle
+/// events.
+///
+/// The device packs active fingers into the lowest slots, so when finger 1
+/// lifts while finger 2 stays, finger 2 moves into slot 1. The tracker matches
+/// each frame against the previous one by distance and keeps the identifier of
+/// a finger across such swaps.
+///
+/// Feed it from [`Event::touchpoints`] with [`crate::EventFilter::release`] enabled,
+/// or from [`crate::Iqs7211e::touchpoints`] in Stream mode.
+///
+/// Timestamps are supplied by the caller in any unit, e.g. milliseconds of a
+/// monotonic clock, and are only copied into the reported contacts.
+///
+/// # Example
+/// ```no_run
+/// # async fn run<I: embedded_hal_async::i2c::I2c, R: embedded_hal_async::digital::Wait>(
+/// #   mut dev: iqs7211e::Iqs7211e<I, R>, now: impl Fn() -> u64,
+/// # ) {
+/// use iqs7211e::{Phase, TouchTracker};
+///
+/// let mut tracker = TouchTracker::new();
+/// loop {
+///   let event = dev.next_event().await.unwrap();
+///   let Some(touchpoints) = event.touchpoints() else { continue };
+///   for change in tracker.update(touchpoints, now()) {
+///     match change.phase {
+///       Phase::Down | Phase::Move => { /* report change.contact */ }
+///       Phase::Up => { /* release change.contact.id */ }
+///     }
+///   }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TouchTracker {
+  slots: [Option<Contact>; SLOTS],
+  next_id: u8,
+}
+
+impl TouchTracker {
+  pub const fn new() -> Self {
+    Self { slots: [None; SLOTS], next_id: 0 }
+  }
+
+  /// Contacts currently on the pad, as of the last frame.
+  pub fn contacts(&self) -> impl Iterator<Item = &Contact> {
+    self.slots.iter().flatten()
+  }
+
+  /// Feed one frame of finger slots captured at `timestamp`.
+  pub fn update(&mut self, touchpoints: Touchpoints, timestamp: u64) -> ContactEvents {
+    let current = [touchpoints.primary, touchpoints.secondary].map(|t| (!t.is_empty()).then_some(t));
+    let previous = self.slots;
+
+    // Pair previous contacts with current slots either in order or crossed,
+    // preferring the pairing that keeps more contacts and then moves them less.
+    let rank = |cross: usize| {
+      let mut pairs = 0;
+      let mut cost = 0u64;
+      for (i, contact) in previous.iter().enumerate() {
+        if let (Some(contact), Some(touch)) = (contact, current[i ^ cross]) {
+          pairs += 1;
+          cost += distance(contact.point, touch.point);
+        }
+      }
+      (core::cmp::Reverse(pairs), cost)
+    };
+    let cross = usize::from(rank(1) < rank(0));
+
+    let mut events = ContactEvents::new();
+    let mut slots = [None; SLOTS];
+    for (i, contact) in previous.iter().enumerate() {
+      let slot = i ^ cross;
+      match (contact, current[slot]) {
+        (Some(contact), Some(touch)) => {
+          let moved = Contact { point: touch.point, strength: touch.strength, area: touch.area, timestamp, ..*contact };
+          if !same_state(contact, &moved) {
+            events.push(ContactEvent { phase: Phase::Move, contact: moved });
+          }
+          slots[slot] = Some(moved);
+        }
+        (Some(contact), None) => {
+          let lifted = Contact { timestamp, ..*contact };
+          events.push(ContactEvent { phase: Phase::Up, contact: lifted });
+        }
+        (None, _) => {}
+      }
+    }
+
+    for (slot, touch) in current.iter().enumerate() {
+      if let (None, Some(touch)) = (slots[slot], touch) {
+        let contact = Contact {
+          id: self.allocate_id(&slots),
+          point: touch.point,
+          strength: touch.strength,
+          area: touch.area,
+          down_at: timestamp,
+          timestamp,
+        };
+        events.push(ContactEvent { phase: Phase::Down, contact });
+        slots[slot] = Some(contact);
+      }
+    }
+
+    self.slots = slots;
+    events
+  }
+
+  /// Lift every tracked contact, e.g. after [`Event::DeviceReset`].
+  pub fn release_all(&mut self, timestamp: u64) -> ContactEvents {
+    let empty = Touchpoints { primary: Touchpoint::EMPTY, secondary: Touchpoint::EMPTY };
+    self.update(empty, timestamp)
+  }
+
+  fn allocate_id(&mut self, slots: &[Option<Contact>; SLOTS]) -> u8 {
+    loop {
+      let id = self.next_id;
+      self.next_id = self.next_id.wrapping_add(1);
+      if !slots.iter().flatten().any(|c| c.id == id) {
+        return id;
+      }
+    }
+  }
+}
+
+fn same_state(a: &Contact, b: &Contact) -> bool {
+  a.point == b.point && a.strength == b.strength && a.area == b.area
+}
+
+fn distance(a: Point, b: Point) -> u64 {
+  let dx = u64::from(a.x.abs_diff(b.x));
+  let dy = u64::from(a.y.abs_diff(b.y));
+  dx * dx + dy * dy
+}
+
+impl Event {
+  /// Finger slots carried by a contact event, for feeding a [`TouchTracker`].
+  ///
+  /// [`Event::Release`] maps to empty slots; events without contact data
+  /// return `None`.
+  pub fn touchpoints(&self) -> Option<Touchpoints> {
+    match *self {
+      Event::Touch(primary, _) => Some(Touchpoints { primary, secondary: Touchpoint::EMPTY }),
+      Event::MultiTouch(primary, secondary, _) => Some(Touchpoints { primary, secondary }),
+      Event::Release => Some(Touchpoints { primary: Touchpoint::EMPTY, secondary: Touchpoint::EMPTY }),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  type Slot = Option<(u16, u16, u16)>;
+
+  fn frame(primary: Slot, secondary: Slot) -> Touchpoints {
+    let touch = |slot: Slot| match slot {
+      Some((x, y, strength)) => Touchpoint { point: Point::new(x, y), strength, area: 4 },
+      None => Touchpoint::EMPTY,
+    };
+    Touchpoints { primary: touch(primary), secondary: touch(secondary) }
+  }
+
+  type Entry = (u64, Phase, u8, u16, u16);
+
+  /// Replay `frames` at 10-tick intervals and compare every event, flattened
+  /// into `(timestamp, phase, id, x, y)`, with `expected`.
+  fn replay(frames: &[(Slot, Slot)], expected: &[Entry]) {
+    let mut tracker = TouchTracker::new();
+    let mut seen = 0;
+    for (i, &(primary, secondary)) in frames.iter().enumerate() {
+      for event in tracker.update(frame(primary, secondary), i as u64 * 10) {
+        let c = event.contact;
+        assert_eq!(Some(&(c.timestamp, event.phase, c.id, c.point.x, c.point.y)), expected.get(seen));
+        seen += 1;
+      }
+    }
+    assert_eq!(seen, expected.len());
+  }
+
+  #[test]
+  fn single_finger_lifecycle() {
+    replay(
+      &[
+        (None, None),
+        (Some((100, 200, 50)), None),
+        (Some((100, 200, 50)), None),
+        (Some((110, 205, 60)), None),
+        (None, None),
+      ],
+      &[
+        (10, Phase::Down, 0, 100, 200),
+        (30, Phase::Move, 0, 110, 205),
+        (40, Phase::Up, 0, 110, 205),
+      ],
+    );
+  }
+
+  #[test]
+  fn keeps_id_when_second_finger_shifts_into_first_slot() {
+    replay(
+      &[
+        (Some((100, 100, 50)), None),
+        (Some((100, 100, 50)), Some((800, 600, 50))),
+        (Some((802, 601, 50)), None),
+        (None, None),
+      ],
+      &[
+        (0, Phase::Down, 0, 100, 100),
+        (10, Phase::Down, 1, 800, 600),
+        (20, Phase::Up, 0, 100, 100),
+        (20, Phase::Move, 1, 802, 601),
+        (30, Phase::Up, 1, 802, 601),
+      ],
+    );
+  }
+
+  #[test]
+  fn follows_crossed_slots() {
+    replay(
+      &[
+        (Some((100, 100, 50)), Some((800, 600, 50))),
+        (Some((795, 600, 50)), Some((105, 100, 50))),
+      ],
+      &[
+        (0, Phase::Down, 0, 100, 100),
+        (0, Phase::Down, 1, 800, 600),
+        (10, Phase::Move, 0, 105, 100),
+        (10, Phase::Move, 1, 795, 600),
+      ],
+    );
+  }
+
+  #[test]
+  fn new_contact_gets_fresh_id_and_down_time() {
+    let mut tracker = TouchTracker::new();
+    let _ = tracker.update(frame(Some((1, 1, 50)), None), 5);
+    let _ = tracker.update(frame(None, None), 6);
+    let mut events = tracker.update(frame(Some((1, 1, 50)), None), 7);
+
+    let down = events.next().unwrap();
+    assert_eq!(down.phase, Phase::Down);
+    assert_eq!((down.contact.id, down.contact.down_at), (1, 7));
+    assert!(events.next().is_none());
+  }
+
+  #[test]
+  fn release_all_lifts_tracked_contacts() {
+    let mut tracker = TouchTracker::new();
+    let _ = tracker.update(frame(Some((1, 1, 50)), Some((9, 9, 50))), 0);
+    assert_eq!(tracker.contacts().count(), 2);
+
+    let lifted = tracker.release_all(3);
+    assert!(lifted.clone().all(|e| e.phase == Phase::Up && e.contact.timestamp == 3));
+    assert_eq!(lifted.count(), 2);
+    assert_eq!(tracker.contacts().count(), 0);
+  }
+}
@@ -0,0 +1,139 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::{Error, Iqs7211e};
+
+/// Trackpad channels covered by the extended pages.
+pub(crate) const MAX_TRACKPAD_CHANNELS: usize = 42;
+
+/// Extended pages holding one 16-bit word per trackpad channel.
+pub(crate) const COUNTS: u16 = 0xE000;
+pub(crate) const REFERENCES: u16 = 0xE100;
+pub(crate) const DELTAS: u16 = 0xE200;
+const COMPENSATION: u16 = 0xE300;
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Capture the count, reference, delta and ATI compensation of every
+  /// trackpad channel, laid out by the configured [`crate::Pinout`].
+  ///
+  /// All four extended pages are burst-read in one communication window, so
+  /// they belong to the same report cycle. Works in any mode, e.g. to stream
+  /// a live heatmap; in Event mode a window is requested first.
+  pub async fn capture_frame(&mut self) -> Result<Frame, Error<E>> {
+    let rx = self.config.pinout.rx.len;
+    let tx = self.config.pinout.tx.len;
+    let channels = rx * tx;
+
+    self.open_comm_window().await?;
+    let len = 2 * channels.min(MAX_TRACKPAD_CHANNELS);
+    let mut pages = [[0u8; 2 * MAX_TRACKPAD_CHANNELS]; 4];
+    let [counts, references, deltas, compensation] = &mut pages;
+    self
+      .read_ext_pages([
+        (COUNTS, &mut counts[..len]),
+        (REFERENCES, &mut references[..len]),
+        (DELTAS, &mut deltas[..len]),
+        (COMPENSATION, &mut compensation[..len]),
+      ])
+      .await?;
+    let [counts, references, deltas, compensation] = pages.map(|page| words(&page));
+
+    Ok(Frame {
+      counts: ChannelMatrix::new(counts, rx, tx),
+      references: ChannelMatrix::new(references, rx, tx),
+      deltas: ChannelMatrix::new(deltas.map(|delta| delta as i16), rx, tx),
+      compensation: ChannelMatrix::new(compensation, rx, tx),
+    })
+  }
+
+  /// Read the first `channels` words of an extended page in one transfer.
+  pub(crate) async fn read_ext_page(
+    &mut self,
+    page: u16,
+    channels: usize,
+  ) -> Result<[u16; MAX_TRACKPAD_CHANNELS], Error<E>> {
+    let channels = channels.min(MAX_TRACKPAD_CHANNELS);
+    let mut buf = [0u8; 2 * MAX_TRACKPAD_CHANNELS];
+    self.read_ext_bytes(page, &mut buf[..2 * channels]).await?;
+    Ok(words(&buf))
+  }
+}
+
+/// Little-endian words of an extended page.
+fn words(buf: &[u8; 2 * MAX_TRACKPAD_CHANNELS]) -> [u16; MAX_TRACKPAD_CHANNELS] {
+  let mut out = [0u16; MAX_TRACKPAD_CHANNELS];
+  for (word, bytes) in out.iter_mut().zip(buf.chunks_exact(2)) {
+    *word = u16::from_le_bytes([bytes[0], bytes[1]]);
+  }
+  out
+}
+
+/// Per-channel measurements of one report cycle, see
+/// [`Iqs7211e::capture_frame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+  /// Raw counts (0xE000).
+  pub counts: ChannelMatrix<u16>,
+  /// Long-term averages the deltas are measured against (0xE100).
+  pub references: ChannelMatrix<u16>,
+  /// Count minus reference (0xE200); positive under a finger.
+  pub deltas: ChannelMatrix<i16>,
+  /// ATI compensation (0xE300).
+  pub compensation: ChannelMatrix<u16>,
+}
+
+/// One value per trackpad channel, addressed by `(rx, tx)`.
+///
+/// `rx` and `tx` are positions in the [`crate::Pinout`] Rx and Tx lists.
+/// Values are stored Tx-major like the device numbers its channels, so each
+/// [`ChannelMatrix::row`] holds the channels of one Tx electrode. Only the
+/// first 42 channels are stored; accessors stop there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelMatrix<T> {
+  values: [T; MAX_TRACKPAD_CHANNELS],
+  rx: usize,
+  tx: usize,
+}
+
+impl<T: Copy> ChannelMatrix<T> {
+  pub(crate) fn new(values: [T; MAX_TRACKPAD_CHANNELS], rx: usize, tx: usize) -> Self {
+    Self { values, rx, tx }
+  }
+
+  pub fn rx_count(&self) -> usize {
+    self.rx
+  }
+
+  pub fn tx_count(&self) -> usize {
+    self.tx
+  }
+
+  /// Value of the channel at `(rx, tx)`, if it is on the trackpad.
+  pub fn get(&self, rx: usize, tx: usize) -> Option<T> {
+    let index = (rx < self.rx && tx < self.tx).then_some(tx * self.rx + rx)?;
+    self.values.get(index).copied()
+  }
+
+  /// Values of the channels along Tx electrode `tx`, in Rx order.
+  pub fn row(&self, tx: usize) -> Option<&[T]> {
+    let start = tx * self.rx;
+    let end = (start + self.rx).min(MAX_TRACKPAD_CHANNELS);
+    (tx < self.tx && start <= end).then(|| &self.values[start..end])
+  }
+
+  /// Rows in Tx order, e.g. to draw a heatmap line by line.
+  pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+    (0..self.tx).filter_map(|tx| self.row(tx))
+  }
+
+  /// All channels in device order.
+  pub fn as_slice(&self) -> &[T] {
+    &self.values[..(self.rx * self.tx).min(MAX_TRACKPAD_CHANNELS)]
+  }
+}
@@ -0,0 +1,589 @@
+//! USB HID reports for forwarding the trackpad to a host.
+//!
+//! Two report flavours are provided, each with its descriptor:
+//!
+//! - [`Mouse`]: a relative boot-style mouse fed from the RelativeX/RelativeY
+//!   registers, with taps turned into button clicks. Works on every host.
+//! - [`PrecisionTouchpad`]: an absolute two-contact report following the
+//!   Windows Precision Touchpad protocol, fed from [`Touchpoints`].
+//!
+//! The descriptors use distinct report IDs, so both can be concatenated into
+//! one interface. Windows starts a precision touchpad in mouse mode and
+//! switches it over through the [`REPORT_ID_INPUT_MODE`] feature report, see
+//! [`InputMode`].
+//!
+//! ```no_run
+//! use iqs7211e::hid::{Mouse, PhysicalSize, PrecisionTouchpad, Scale};
+//! use iqs7211e::{Resolution, Touchpoints};
+//!
+//! let resolution = Resolution::new(1000, 1000);
+//! let ptp = PrecisionTouchpad::new(Scale::new(resolution, Resolution::new(4095, 4095)));
+//! // Hand these to the USB stack when the host asks for the report descriptor.
+//! let descriptor = ptp.report_descriptor(PhysicalSize::new(650, 650));
+//!
+//! fn on_frame(ptp: &mut PrecisionTouchpad, touchpoints: Touchpoints, scan_time: u16, send: impl Fn(&[u8])) {
+//!   send(&ptp.report(touchpoints, false, scan_time).to_bytes());
+//! }
+//! ```
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::{Contact, Error, Gesture, Iqs7211e, Phase, Point, Resolution, TapCount, TouchTracker, Touchpoints, Vector};
+
+/// Input report of [`Mouse`].
+pub const REPORT_ID_MOUSE: u8 = 0x01;
+/// Input report of [`PrecisionTouchpad`].
+pub const REPORT_ID_TOUCHPAD: u8 = 0x02;
+/// Feature report answered with [`PrecisionTouchpad::capabilities`].
+pub const REPORT_ID_CAPABILITIES: u8 = 0x03;
+/// Feature report holding the 256-byte Windows certification blob.
+pub const REPORT_ID_CERTIFICATION: u8 = 0x04;
+/// Feature report the host writes to pick an [`InputMode`].
+pub const REPORT_ID_INPUT_MODE: u8 = 0x05;
+/// Feature report the host writes to enable surface and button reporting.
+pub const REPORT_ID_FUNCTION_SWITCH: u8 = 0x06;
+
+/// Contacts carried by one precision touchpad report.
+const CONTACTS: usize = 2;
+/// Length of [`MouseReport::to_bytes`].
+pub const MOUSE_REPORT_LEN: usize = 4;
+/// Length of [`TouchpadReport::to_bytes`].
+pub const TOUCHPAD_REPORT_LEN: usize = 1 + CONTACTS * 6 + 2 + 1 + 1;
+/// Length of [`PrecisionTouchpad::report_descriptor`].
+pub const TOUCHPAD_DESCRIPTOR_LEN: usize = 288;
+
+/// Report descriptor of [`Mouse`]: three buttons and 8-bit relative X/Y.
+#[rustfmt::skip]
+pub const MOUSE_REPORT_DESCRIPTOR: [u8; 52] = [
+  0x05, 0x01, // Usage Page (Generic Desktop)
+  0x09, 0x02, // Usage (Mouse)
+  0xA1, 0x01, // Collection (Application)
+  0x85, REPORT_ID_MOUSE, //   Report ID
+  0x09, 0x01, //   Usage (Pointer)
+  0xA1, 0x00, //   Collection (Physical)
+  0x05, 0x09, //     Usage Page (Button)
+  0x19, 0x01, //     Usage Minimum (1)
+  0x29, 0x03, //     Usage Maximum (3)
+  0x15, 0x00, //     Logical Minimum (0)
+  0x25, 0x01, //     Logical Maximum (1)
+  0x95, 0x03, //     Report Count (3)
+  0x75, 0x01, //     Report Size (1)
+  0x81, 0x02, //     Input (Data, Variable, Absolute)
+  0x95, 0x01, //     Report Count (1)
+  0x75, 0x05, //     Report Size (5)
+  0x81, 0x03, //     Input (Constant)
+  0x05, 0x01, //     Usage Page (Generic Desktop)
+  0x09, 0x30, //     Usage (X)
+  0x09, 0x31, //     Usage (Y)
+  0x15, 0x81, //     Logical Minimum (-127)
+  0x25, 0x7F, //     Logical Maximum (127)
+  0x75, 0x08, //     Report Size (8)
+  0x95, 0x02, //     Report Count (2)
+  0x81, 0x06, //     Input (Data, Variable, Relative)
+  0xC0, //   End Collection
+  0xC0, // End Collection
+];
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Read the relative movement and gesture of the current cycle and encode
+  /// them with `mouse`. Reads from the open communication window, if any.
+  pub async fn mouse_report(&mut self, mouse: &mut Mouse) -> Result<MouseReport, Error<E>> {
+    let (motion, gesture) = self.motion_and_gesture().await?;
+    Ok(mouse.report(motion, gesture))
+  }
+}
+
+/// Linear mapping from trackpad coordinates to report units.
+#[derive(Debug, Clone, Copy)]
+pub struct Scale {
+  from: Resolution,
+  to: Resolution,
+}
+
+impl Scale {
+  /// Map `0..=from` on each axis, usually [`crate::Trackpad::resolution`], onto
+  /// `0..=to`.
+  pub const fn new(from: Resolution, to: Resolution) -> Self {
+    Self { from, to }
+  }
+
+  /// Keep trackpad coordinates as they are.
+  pub const fn identity(resolution: Resolution) -> Self {
+    Self::new(resolution, resolution)
+  }
+
+  /// Scale an absolute position, clamping it to the target range.
+  pub fn point(&self, point: Point) -> Point {
+    Point::new(
+      scale(point.x.min(self.from.x) as i32, self.from.x, self.to.x) as u16,
+      scale(point.y.min(self.from.y) as i32, self.from.y, self.to.y) as u16,
+    )
+  }
+
+  /// Scale a relative movement.
+  pub fn vector(&self, vector: Vector) -> (i32, i32) {
+    (scale(vector.dx as i32, self.from.x, self.to.x), scale(vector.dy as i32, self.from.y, self.to.y))
+  }
+}
+
+fn scale(value: i32, from: u16, to: u16) -> i32 {
+  match from {
+    0 => 0,
+    from => value * to as i32 / from as i32,
+  }
+}
+
+/// Button bits of a [`MouseReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Buttons(pub u8);
+
+impl Buttons {
+  pub const NONE: Self = Self(0);
+  pub const LEFT: Self = Self(1 << 0);
+  pub const RIGHT: Self = Self(1 << 1);
+  pub const MIDDLE: Self = Self(1 << 2);
+}
+
+/// Relative mouse input report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MouseReport {
+  pub buttons: Buttons,
+  pub x: i8,
+  pub y: i8,
+}
+
+impl MouseReport {
+  /// Encode the report, prefixed with [`REPORT_ID_MOUSE`].
+  pub fn to_bytes(&self) -> [u8; MOUSE_REPORT_LEN] {
+    [REPORT_ID_MOUSE, self.buttons.0, self.x as u8, self.y as u8]
+  }
+}
+
+/// Encodes relative movement and taps as [`MouseReport`]s.
+///
+/// A one-finger tap clicks the left button, a two-finger tap the right one and
+/// a three-finger tap the middle one. The click is released by the next
+/// report, or by [`Mouse::release`] when no movement follows.
+#[derive(Debug, Clone, Copy)]
+pub struct Mouse {
+  scale: Scale,
+  pressed: bool,
+}
+
+impl Mouse {
+  pub const fn new(scale: Scale) -> Self {
+    Self { scale, pressed: false }
+  }
+
+  /// Encode one cycle of movement and its gesture, if any.
+  pub fn report(&mut self, motion: Vector, gesture: Option<Gesture>) -> MouseReport {
+    let buttons = match gesture {
+      Some(Gesture::Tap(TapCount::One, _)) => Buttons::LEFT,
+      Some(Gesture::Tap(TapCount::Two, _)) => Buttons::RIGHT,
+      Some(Gesture::Tap(TapCount::Three, _)) => Buttons::MIDDLE,
+      _ => Buttons::NONE,
+    };
+    self.pressed = buttons != Buttons::NONE;
+
+    let (x, y) = self.scale.vector(motion);
+    MouseReport { buttons, x: x.clamp(-127, 127) as i8, y: y.clamp(-127, 127) as i8 }
+  }
+
+  /// Report releasing the buttons pressed by the last tap, if any.
+  pub fn release(&mut self) -> Option<MouseReport> {
+    match core::mem::take(&mut self.pressed) {
+      true => Some(MouseReport::default()),
+      false => None,
+    }
+  }
+}
+
+/// Physical size of the sensing area, in tenths of a millimetre.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicalSize {
+  pub width: u16,
+  pub height: u16,
+}
+
+impl PhysicalSize {
+  pub const fn new(width: u16, height: u16) -> Self {
+    Self { width, height }
+  }
+}
+
+/// Mechanical style of the touchpad, reported in the capabilities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadType {
+  /// The whole pad clicks down onto a switch.
+  Depressible = 0,
+  /// Clicks are detected from pressure.
+  Pressure = 1,
+  /// No click; buttons, if any, are separate.
+  NonClickable = 2,
+}
+
+/// Reporting mode selected by the host through [`REPORT_ID_INPUT_MODE`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+  /// Send [`MouseReport`]s.
+  Mouse = 0,
+  /// Send [`TouchpadReport`]s.
+  Touchpad = 3,
+}
+
+impl TryFrom<&[u8]> for InputMode {
+  type Error = ();
+
+  /// Decode the feature report written by the host, report ID included.
+  fn try_from(report: &[u8]) -> Result<Self, Self::Error> {
+    match report {
+      [REPORT_ID_INPUT_MODE, 0, ..] => Ok(Self::Mouse),
+      [REPORT_ID_INPUT_MODE, 3, ..] => Ok(Self::Touchpad),
+      _ => Err(()),
+    }
+  }
+}
+
+/// One contact slot of a [`TouchpadReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TouchpadContact {
+  /// The contact is a finger rather than e.g. a palm.
+  pub confidence: bool,
+  /// The contact is on the surface; cleared once when it lifts.
+  pub tip: bool,
+  pub id: u8,
+  pub x: u16,
+  pub y: u16,
+}
+
+/// Windows Precision Touchpad input report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TouchpadReport {
+  pub contacts: [TouchpadContact; CONTACTS],
+  /// Time of the frame in units of 100 µs, wrapping.
+  pub scan_time: u16,
+  /// Valid entries in `contacts`.
+  pub contact_count: u8,
+  pub button: bool,
+}
+
+impl TouchpadReport {
+  /// Encode the report, prefixed with [`REPORT_ID_TOUCHPAD`].
+  pub fn to_bytes(&self) -> [u8; TOUCHPAD_REPORT_LEN] {
+    let mut out = [0u8; TOUCHPAD_REPORT_LEN];
+    out[0] = REPORT_ID_TOUCHPAD;
+    for (i, contact) in self.contacts.iter().enumerate() {
+      let at = 1 + i * 6;
+      out[at] = contact.confidence as u8 | (contact.tip as u8) << 1;
+      out[at + 1] = contact.id;
+      out[at + 2..at + 4].copy_from_slice(&contact.x.to_le_bytes());
+      out[at + 4..at + 6].copy_from_slice(&contact.y.to_le_bytes());
+    }
+    let at = 1 + CONTACTS * 6;
+    out[at..at + 2].copy_from_slice(&self.scan_time.to_le_bytes());
+    out[at + 2] = self.contact_count;
+    out[at + 3] = self.button as u8;
+    out
+  }
+}
+
+/// Encodes [`Touchpoints`] as Windows Precision Touchpad reports.
+///
+/// Contacts keep their identifier across frames through a [`TouchTracker`],
+/// and a lifted contact is reported once with its tip cleared. Contacts still
+/// on the pad come first; a lift that does not fit next to them is sent with
+/// the next report that has room.
+#[derive(Debug, Clone, Copy)]
+pub struct PrecisionTouchpad {
+  scale: Scale,
+  tracker: TouchTracker,
+  /// Lifts left out of the previous report.
+  lifted: [Option<Contact>; CONTACTS],
+}
+
+impl PrecisionTouchpad {
+  pub const fn new(scale: Scale) -> Self {
+    Self { scale, tracker: TouchTracker::new(), lifted: [None; CONTACTS] }
+  }
+
+  /// Encode one frame. `button` is the state of the pad's click switch, if
+  /// any, and `scan_time` the frame time in units of 100 µs.
+  pub fn report(&mut self, touchpoints: Touchpoints, button: bool, scan_time: u16) -> TouchpadReport {
+    let deferred = core::mem::replace(&mut self.lifted, [None; CONTACTS]);
+    let events = self.tracker.update(touchpoints, scan_time as u64);
+    let ups = events
+      .filter(|event| event.phase == Phase::Up)
+      .map(|event| event.contact);
+    let lifts = deferred
+      .into_iter()
+      .flatten()
+      .chain(ups)
+      .map(|contact| (false, contact));
+    let live = self.tracker.contacts().map(|&contact| (true, contact));
+
+    let mut report = TouchpadReport { scan_time, button, ..TouchpadReport::default() };
+    let mut count = 0;
+    for (tip, contact) in live.chain(lifts) {
+      if count < CONTACTS {
+        let point = self.scale.point(contact.point);
+        report.contacts[count] = TouchpadContact { confidence: true, tip, id: contact.id, x: point.x, y: point.y };
+        count += 1;
+      } else if let Some(free) = self.lifted.iter_mut().find(|free| free.is_none()) {
+        *free = Some(contact);
+      }
+    }
+    report.contact_count = count as u8;
+    report
+  }
+
+  /// Payload of the [`REPORT_ID_CAPABILITIES`] feature report.
+  pub fn capabilities(&self, pad: PadType) -> [u8; 2] {
+    [REPORT_ID_CAPABILITIES, CONTACTS as u8 | (pad as u8) << 4]
+  }
+
+  /// Report descriptor of the touchpad collections, with the logical range
+  /// taken from the target of the [`Scale`].
+  pub fn report_descriptor(&self, size: PhysicalSize) -> [u8; TOUCHPAD_DESCRIPTOR_LEN] {
+    let [lx0, lx1] = self.scale.to.x.to_le_bytes();
+    let [ly0, ly1] = self.scale.to.y.to_le_bytes();
+    let [px0, px1] = size.width.to_le_bytes();
+    let [py0, py1] = size.height.to_le_bytes();
+
+    #[rustfmt::skip]
+    let finger = [
+      0x05, 0x0D, //   Usage Page (Digitizer)
+      0x09, 0x22, //   Usage (Finger)
+      0xA1, 0x02, //   Collection (Logical)
+      0x15, 0x00, //     Logical Minimum (0)
+      0x25, 0x01, //     Logical Maximum (1)
+      0x09, 0x47, //     Usage (Confidence)
+      0x09, 0x42, //     Usage (Tip Switch)
+      0x95, 0x02, //     Report Count (2)
+      0x75, 0x01, //     Report Size (1)
+      0x81, 0x02, //     Input (Data, Variable, Absolute)
+      0x95, 0x06, //     Report Count (6)
+      0x81, 0x03, //     Input (Constant)
+      0x26, 0xFF, 0x00, // Logical Maximum (255)
+      0x75, 0x08, //     Report Size (8)
+      0x95, 0x01, //     Report Count (1)
+      0x09, 0x51, //     Usage (Contact Identifier)
+      0x81, 0x02, //     Input (Data, Variable, Absolute)
+      0x05, 0x01, //     Usage Page (Generic Desktop)
+      0x75, 0x10, //     Report Size (16)
+      0x55, 0x0E, //     Unit Exponent (-2)
+      0x65, 0x11, //     Unit (Centimetre)
+      0x35, 0x00, //     Physical Minimum (0)
+      0x26, lx0, lx1, // Logical Maximum
+      0x46, px0, px1, // Physical Maximum
+      0x09, 0x30, //     Usage (X)
+      0x81, 0x02, //     Input (Data, Variable, Absolute)
+      0x26, ly0, ly1, // Logical Maximum
+      0x46, py0, py1, // Physical Maximum
+      0x09, 0x31, //     Usage (Y)
+      0x81, 0x02, //     Input (Data, Variable, Absolute)
+      0x55, 0x00, //     Unit Exponent (0)
+      0x65, 0x00, //     Unit (None)
+      0x45, 0x00, //     Physical Maximum (0)
+      0xC0, //   End Collection
+    ];
+
+    #[rustfmt::skip]
+    let head = [
+      0x05, 0x0D, // Usage Page (Digitizer)
+      0x09, 0x05, // Usage (Touch Pad)
+      0xA1, 0x01, // Collection (Application)
+      0x85, REPORT_ID_TOUCHPAD, // Report ID
+    ];
+
+    #[rustfmt::skip]
+    let tail = [
+      0x05, 0x0D, //   Usage Page (Digitizer)
+      0x55, 0x0C, //   Unit Exponent (-4)
+      0x66, 0x01, 0x10, // Unit (Seconds)
+      0x47, 0xFF, 0xFF, 0x00, 0x00, // Physical Maximum (65535)
+      0x27, 0xFF, 0xFF, 0x00, 0x00, // Logical Maximum (65535)
+      0x75, 0x10, //   Report Size (16)
+      0x95, 0x01, //   Report Count (1)
+      0x09, 0x56, //   Usage (Scan Time)
+      0x81, 0x02, //   Input (Data, Variable, Absolute)
+      0x55, 0x00, //   Unit Exponent (0)
+      0x65, 0x00, //   Unit (None)
+      0x45, 0x00, //   Physical Maximum (0)
+      0x09, 0x54, //   Usage (Contact Count)
+      0x25, 0x7F, //   Logical Maximum (127)
+      0x75, 0x08, //   Report Size (8)
+      0x81, 0x02, //   Input (Data, Variable, Absolute)
+      0x05, 0x09, //   Usage Page (Button)
+      0x09, 0x01, //   Usage (Button 1)
+      0x25, 0x01, //   Logical Maximum (1)
+      0x75, 0x01, //   Report Size (1)
+      0x81, 0x02, //   Input (Data, Variable, Absolute)
+      0x95, 0x07, //   Report Count (7)
+      0x81, 0x03, //   Input (Constant)
+      0x05, 0x0D, //   Usage Page (Digitizer)
+      0x85, REPORT_ID_CAPABILITIES, // Report ID
+      0x09, 0x55, //   Usage (Contact Count Maximum)
+      0x09, 0x59, //   Usage (Pad Type)
+      0x25, 0x0F, //   Logical Maximum (15)
+      0x75, 0x04, //   Report Size (4)
+      0x95, 0x02, //   Report Count (2)
+      0xB1, 0x02, //   Feature (Data, Variable, Absolute)
+      0x06, 0x00, 0xFF, // Usage Page (Vendor Defined)
+      0x85, REPORT_ID_CERTIFICATION, // Report ID
+      0x09, 0xC5, //   Usage (Certification Blob)
+      0x26, 0xFF, 0x00, // Logical Maximum (255)
+      0x75, 0x08, //   Report Size (8)
+      0x96, 0x00, 0x01, // Report Count (256)
+      0xB1, 0x02, //   Feature (Data, Variable, Absolute)
+      0xC0, // End Collection
+      0x05, 0x0D, // Usage Page (Digitizer)
+      0x09, 0x0E, // Usage (Device Configuration)
+      0xA1, 0x01, // Collection (Application)
+      0x85, REPORT_ID_INPUT_MODE, // Report ID
+      0x09, 0x22, //   Usage (Finger)
+      0xA1, 0x02, //   Collection (Logical)
+      0x09, 0x52, //     Usage (Input Mode)
+      0x15, 0x00, //     Logical Minimum (0)
+      0x25, 0x0A, //     Logical Maximum (10)
+      0x75, 0x08, //     Report Size (8)
+      0x95, 0x01, //     Report Count (1)
+      0xB1, 0x02, //     Feature (Data, Variable, Absolute)
+      0xC0, //   End Collection
+      0x09, 0x22, //   Usage (Finger)
+      0xA1, 0x00, //   Collection (Physical)
+      0x85, REPORT_ID_FUNCTION_SWITCH, // Report ID
+      0x09, 0x57, //     Usage (Surface Switch)
+      0x09, 0x58, //     Usage (Button Switch)
+      0x25, 0x01, //     Logical Maximum (1)
+      0x75, 0x01, //     Report Size (1)
+      0x95, 0x02, //     Report Count (2)
+      0xB1, 0x02, //     Feature (Data, Variable, Absolute)
+      0x95, 0x06, //     Report Count (6)
+      0xB1, 0x03, //     Feature (Constant)
+      0xC0, //   End Collection
+      0xC0, // End Collection
+    ];
+
+    let mut out = [0u8; TOUCHPAD_DESCRIPTOR_LEN];
+    let mut at = 0;
+    for part in [&head[..], &finger, &finger, &tail] {
+      out[at..at + part.len()].copy_from_slice(part);
+      at += part.len();
+    }
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Touchpoint;
+
+  fn frame(primary: Option<(u16, u16)>, secondary: Option<(u16, u16)>) -> Touchpoints {
+    let touch = |slot: Option<(u16, u16)>| match slot {
+      Some((x, y)) => Touchpoint { point: Point::new(x, y), strength: 50, area: 4 },
+      None => Touchpoint::EMPTY,
+    };
+    Touchpoints { primary: touch(primary), secondary: touch(secondary) }
+  }
+
+  /// Walk the short items of a descriptor, check that every collection is
+  /// closed and count the input bits of `report_id`.
+  fn input_bits(descriptor: &[u8], report_id: u8) -> u32 {
+    let (mut at, mut depth, mut size, mut count, mut id, mut bits) = (0, 0i32, 0u32, 0u32, 0u8, 0u32);
+    while at < descriptor.len() {
+      let prefix = descriptor[at];
+      let len = match prefix & 0b11 {
+        3 => 4,
+        n => n as usize,
+      };
+      let data = descriptor[at + 1..at + 1 + len]
+        .iter()
+        .rev()
+        .fold(0u32, |v, &b| v << 8 | b as u32);
+      match prefix & 0xFC {
+        0xA0 => depth += 1,
+        0xC0 => depth -= 1,
+        0x74 => size = data,
+        0x94 => count = data,
+        0x84 => id = data as u8,
+        0x80 if id == report_id => bits += size * count,
+        _ => {}
+      }
+      at += 1 + len;
+    }
+    assert_eq!(depth, 0);
+    bits
+  }
+
+  #[test]
+  fn descriptors_match_report_layouts() {
+    assert_eq!(input_bits(&MOUSE_REPORT_DESCRIPTOR, REPORT_ID_MOUSE), (MOUSE_REPORT_LEN as u32 - 1) * 8);
+
+    let ptp = PrecisionTouchpad::new(Scale::identity(Resolution::new(1000, 800)));
+    let descriptor = ptp.report_descriptor(PhysicalSize::new(650, 520));
+    assert_eq!(input_bits(&descriptor, REPORT_ID_TOUCHPAD), (TOUCHPAD_REPORT_LEN as u32 - 1) * 8);
+    assert_eq!(descriptor[descriptor.len() - 2..], [0xC0, 0xC0]);
+  }
+
+  #[test]
+  fn mouse_scales_motion_and_clicks_on_tap() {
+    let mut mouse = Mouse::new(Scale::new(Resolution::new(1000, 1000), Resolution::new(500, 500)));
+
+    let report = mouse.report(Vector::new(40, -1000), None);
+    assert_eq!(report.to_bytes(), [REPORT_ID_MOUSE, 0, 20, (-127i8) as u8]);
+    assert_eq!(mouse.release(), None);
+
+    let report = mouse.report(Vector::new(0, 0), Some(Gesture::Tap(TapCount::Two, Point::new(1, 1))));
+    assert_eq!(report.buttons, Buttons::RIGHT);
+    assert_eq!(mouse.release(), Some(MouseReport::default()));
+    assert_eq!(mouse.release(), None);
+  }
+
+  #[test]
+  fn touchpad_reports_lift_once_and_keeps_ids() {
+    let mut ptp = PrecisionTouchpad::new(Scale::new(Resolution::new(1000, 1000), Resolution::new(2000, 2000)));
+
+    let report = ptp.report(frame(Some((100, 200)), Some((700, 800))), false, 10);
+    assert_eq!(report.contact_count, 2);
+    assert_eq!((report.contacts[0].id, report.contacts[0].x, report.contacts[0].y), (0, 200, 400));
+    assert_eq!((report.contacts[1].id, report.contacts[1].x, report.contacts[1].y), (1, 1400, 1600));
+
+    // Finger 1 lifts; finger 2 shifts into the first slot.
+    let report = ptp.report(frame(Some((700, 800)), None), true, 20);
+    assert_eq!(report.contact_count, 2);
+    assert_eq!((report.contacts[0].id, report.contacts[0].tip), (1, true));
+    assert_eq!((report.contacts[1].id, report.contacts[1].tip), (0, false));
+
+    let bytes = ptp.report(frame(Some((700, 800)), None), true, 30).to_bytes();
+    assert_eq!(bytes[..7], [REPORT_ID_TOUCHPAD, 0b11, 1, 0x78, 0x05, 0x40, 0x06]);
+    assert_eq!(bytes[13..], [30, 0, 1, 1]);
+  }
+
+  #[test]
+  fn touchpad_sends_deferred_lift_after_live_contacts() {
+    let mut ptp = PrecisionTouchpad::new(Scale::identity(Resolution::new(1000, 1000)));
+    let lifted = Contact { id: 7, point: Point::new(5, 6), strength: 50, area: 4, down_at: 0, timestamp: 10 };
+    ptp.lifted[0] = Some(lifted);
+
+    let report = ptp.report(frame(Some((100, 200)), None), false, 20);
+    assert_eq!(report.contact_count, 2);
+    assert_eq!((report.contacts[0].id, report.contacts[0].tip), (0, true));
+    assert_eq!((report.contacts[1].id, report.contacts[1].tip, report.contacts[1].x), (7, false, 5));
+    assert_eq!(ptp.report(frame(Some((100, 200)), None), false, 30).contact_count, 1);
+  }
+
+  #[test]
+  fn input_mode_decodes_host_request() {
+    assert_eq!(InputMode::try_from(&[REPORT_ID_INPUT_MODE, 3][..]), Ok(InputMode::Touchpad));
+    assert_eq!(InputMode::try_from(&[REPORT_ID_INPUT_MODE, 0][..]), Ok(InputMode::Mouse));
+    assert!(InputMode::try_from(&[REPORT_ID_CAPABILITIES, 3][..]).is_err());
+  }
+}
@@ -42,21 +42,39 @@
 //!   Ok(())
 //! }
 //! ```
+#[cfg(feature = "blocking")]
+pub mod blocking;
 mod config;
 mod control;
 mod event;
+mod frame;
+#[cfg(feature = "hid")]
+pub mod hid;
+mod power;
+mod production;
 mod reg;
 mod rw;
 mod setup;
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
+mod timeout;
+#[cfg(any(test, feature = "blocking", feature = "sim"))]
+mod waker;
 
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 use embedded_hal_async::i2c::{I2c, SevenBitAddress};
 
 pub use config::*;
 pub use control::*;
 pub use event::*;
+pub use frame::*;
+pub use power::*;
+pub use production::*;
 use reg::*;
 pub use setup::*;
+pub use timeout::*;
 
 /// Errors that can occur while interacting with the controller.
 #[derive(Debug)]
@@ -67,6 +85,36 @@ pub enum Error<E> {
   InvalidChipId(u8),
   /// An operation attempted to write a buffer larger than the protocol allows.
   BufferOverflow,
+  /// A staged block does not encode into its registers, e.g. a field wider
+  /// than its bits.
+  Encode,
+  /// Registers read back hold values the driver cannot decode, e.g. a pinout
+  /// referencing pins that do not exist.
+  Decode,
+  /// RDY did not open a communication window within [`Deadlines::comm_window_ms`],
+  /// or the device never reported a reset within [`Deadlines::max_polls`] windows.
+  RdyTimeout,
+  /// The ATI routine did not complete within [`Deadlines::max_polls`] windows.
+  AutoTuneTimeout,
+  /// Reading the RDY pin or driving the reset line failed.
+  Pin(embedded_hal::digital::ErrorKind),
+  /// A configuration register read back differently from what was written.
+  ConfigMismatch(ConfigMismatch),
+  /// A hardware reset was requested without a reset line, see
+  /// [`Iqs7211e::with_reset`].
+  NoResetPin,
+  /// A hardware reset was requested without a delay provider to time the
+  /// reset pulse and boot, see [`Iqs7211e::with_delay`].
+  NoDelay,
+  /// The firmware lies outside the range accepted by the [`FirmwarePolicy`].
+  UnsupportedFirmware(DeviceIdentity),
+  /// [`Iqs7211e::next_event`] was called on a device in [`Suspend::Deep`],
+  /// which reports nothing until [`Iqs7211e::resume`], or the charge mode was
+  /// changed while suspended.
+  Suspended,
+  /// The staged configuration breaks a datasheet constraint, see
+  /// [`Config::validate`]. Nothing was written to the device.
+  InvalidConfig(ConfigIssue),
 }
 
 /// High-level state machine for the Azoteq IQS7211E controller.
@@ -75,9 +123,24 @@ pub enum Error<E> {
 /// configuration helpers and control functions. Create an instance with
 /// [`Iqs7211e::new`], provide a [`config::Config`], and then call
 /// [`Iqs7211e::initialize`] to stage the desired setup on the device.
-pub struct Iqs7211e<I, RDY> {
+///
+/// Attach a delay provider with [`Iqs7211e::with_delay`] to bound every wait
+/// on the controller, see [`Deadlines`], and a reset line with
+/// [`Iqs7211e::with_reset`] to enable [`Iqs7211e::hardware_reset`].
+pub struct Iqs7211e<I, RDY, D = NoDelay, RST = NoReset> {
   i2c: I,
   rdy: RDY,
+  delay: D,
+  /// A delay provider was attached, so deadlines can be measured.
+  timed: bool,
+  reset: Option<RST>,
+  deadlines: Deadlines,
+  verify_retries: Option<u8>,
+  firmware: FirmwarePolicy,
+  identity: Option<DeviceIdentity>,
+  suspended: Option<Suspended>,
+  comms: rw::Comms,
+  events: EventState,
   config: config::Config,
 }
 
@@ -93,47 +156,205 @@ where
   /// [`Iqs7211e::initialize`] is called. This allows the caller to adjust fields
   /// after construction if desired.
   pub fn new(i2c: I, rdy: RDY, config: config::Config) -> Self {
-    Self { i2c, rdy, config }
+    Self {
+      i2c,
+      rdy,
+      delay: NoDelay,
+      timed: false,
+      reset: None,
+      deadlines: Deadlines::default(),
+      verify_retries: None,
+      firmware: FirmwarePolicy::any(),
+      identity: None,
+      suspended: None,
+      comms: rw::Comms::default(),
+      events: EventState::new(EventFilter::default()),
+      config,
+    }
+  }
+}
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Attach a delay provider used to time out unresponsive communication.
+  ///
+  /// Enables a default RDY deadline unless one was already configured with
+  /// [`Iqs7211e::with_deadlines`].
+  pub fn with_delay<D2: DelayNs>(self, delay: D2) -> Iqs7211e<I, RDY, D2, RST> {
+    let mut deadlines = self.deadlines;
+    deadlines.comm_window_ms = deadlines.comm_window_ms.or(Some(DEFAULT_COMM_WINDOW_MS));
+    Iqs7211e {
+      i2c: self.i2c,
+      rdy: self.rdy,
+      delay,
+      timed: true,
+      reset: self.reset,
+      deadlines,
+      verify_retries: self.verify_retries,
+      firmware: self.firmware,
+      identity: self.identity,
+      suspended: self.suspended,
+      comms: self.comms,
+      events: self.events,
+      config: self.config,
+    }
+  }
+
+  /// Attach the host output wired to the RDY/MCLR pin.
+  ///
+  /// The IQS7211E shares MCLR with RDY, so the output must be open-drain (or
+  /// otherwise release the line when high) to keep RDY readable.
+  pub fn with_reset<RST2: OutputPin>(self, reset: RST2) -> Iqs7211e<I, RDY, D, RST2> {
+    Iqs7211e {
+      i2c: self.i2c,
+      rdy: self.rdy,
+      delay: self.delay,
+      timed: self.timed,
+      reset: Some(reset),
+      deadlines: self.deadlines,
+      verify_retries: self.verify_retries,
+      firmware: self.firmware,
+      identity: self.identity,
+      suspended: self.suspended,
+      comms: self.comms,
+      events: self.events,
+      config: self.config,
+    }
+  }
+
+  /// Replace the limits applied to communication windows and bring-up phases.
+  ///
+  /// The window deadline is only enforced once a delay provider is attached
+  /// with [`Iqs7211e::with_delay`].
+  pub fn with_deadlines(mut self, deadlines: Deadlines) -> Self {
+    self.deadlines = deadlines;
+    self
+  }
+
+  /// Read the configuration back after every push and rewrite it up to
+  /// `retries` times on a mismatch, see [`Iqs7211e::verify_config`].
+  pub fn with_config_verification(mut self, retries: u8) -> Self {
+    self.verify_retries = Some(retries);
+    self
+  }
+
+  /// Check the firmware revisions against `policy` during
+  /// [`Iqs7211e::initialize`]. Any firmware is accepted by default.
+  pub fn with_firmware_policy(mut self, policy: FirmwarePolicy) -> Self {
+    self.firmware = policy;
+    self
+  }
+
+  /// Choose which state changes [`Iqs7211e::next_event`] reports besides
+  /// gestures and contacts. None of them are reported by default.
+  pub fn with_event_filter(mut self, filter: EventFilter) -> Self {
+    self.events.filter = filter;
+    self
+  }
+
+  /// Configuration staged for, or applied to, the device.
+  pub fn config(&self) -> &config::Config {
+    &self.config
   }
 
   /// Initialize the touchpad controller.
   ///
-  /// This validates the product identifier, handles chip reset if needed,
-  /// pushes the staged configuration, and triggers the ATI calibration routine.
+  /// This validates the product identifier and the firmware revisions against
+  /// the [`FirmwarePolicy`], handles chip reset if needed, pushes the staged
+  /// configuration, and triggers the ATI calibration routine.
   /// Returns `true` if a configuration update occurred during initialization.
+  ///
+  /// With a non-zero [`Config::settings_version`] stamp, a device that did not
+  /// reset since it was configured (e.g. after a host wake from deep sleep)
+  /// and still holds the same stamp keeps its settings and ATI results; no
+  /// reset, push or ATI takes place and `false` is returned. A suspension or
+  /// changed event triggers left behind by the previous driver are undone.
+  ///
+  /// A configuration with errors in [`Config::validate`] is refused with
+  /// [`Error::InvalidConfig`] before the bus is touched.
   pub async fn initialize(&mut self) -> Result<bool, Error<E>> {
+    if let Some(issue) = self.config.validate().first_error() {
+      return Err(Error::InvalidConfig(issue));
+    }
+
     // Device boots in Event Mode with Show Reset set. Since no events are
     // happening yet, RDY stays HIGH. Force first communication window.
     self.force_comms_request().await?;
+    // A device that did not reset may still hold windows open for an end
+    // command, which the driver has to know before the next transfer
+    let (sys_control, settings) = self.system_settings().await?;
+    self.comms = rw::Comms { synced: false, ..rw::Comms::new(settings.end_comms) };
 
-    // Verify chip ID
-    let prod_num = self.app_version().await?.number;
-    if prod_num != PRODUCT_NUMBER {
-      return Err(Error::InvalidChipId(prod_num as u8));
+    // Verify chip ID and firmware
+    let identity = self.device_identity().await?;
+    if identity.product != PRODUCT_NUMBER {
+      return Err(Error::InvalidChipId(identity.product as u8));
+    }
+    self.identity = Some(identity);
+    if self.firmware.action == FirmwareAction::Reject && !self.firmware.supports(&identity) {
+      return Err(Error::UnsupportedFirmware(identity));
     }
 
     // Check if reset occurred
     if !self.info().await?.show_reset {
+      // Warm boot: keep the settings if they carry the staged stamp
+      let stamp = self.config.settings_stamp().ok_or(Error::Encode)?;
+      if stamp != 0 && self.read_u16(Reg::SettingsVersion).await? == stamp {
+        self.restore_system_settings(sys_control, settings).await?;
+        // The restored settings may hold this window open for an end command
+        self.comms = rw::Comms::new(self.config.end_comms);
+        self.end_comm_window().await?;
+        return Ok(false);
+      }
       // No reset detected, request one
       self.software_reset().await?;
-      // Wait for reset to complete - force comms since RDY won't pulse
-      loop {
-        self.wait_for_comm_window().await?;
-        if self.info().await?.show_reset {
-          break;
-        }
-      }
+      // Wait for the reset to complete; the device opens a window once it has booted
+      self.poll_info(|info| info.show_reset).await?.ok_or(Error::RdyTimeout)?;
     }
 
+    self.configure().await?;
+    Ok(true)
+  }
+
+  /// Push the staged configuration to a freshly reset device, acknowledge the
+  /// reset and run ATI.
+  async fn configure(&mut self) -> Result<(), Error<E>> {
+    // The reset woke the device and restored its settings
+    self.suspended = None;
+    self.comms = rw::Comms::default();
+
     // Switch to Stream Mode for initialization so RDY pulses every cycle
-    // This avoids having to force comms repeatedly
-    self.set_interrupt_mode(InterruptMode::Stream).await?;
+    // This avoids having to force comms repeatedly. The staged mode is left
+    // alone, it is written once the device is configured
+    self.open_comm_window().await?;
+    self
+      .modify_config_settings(|settings| settings.interrupt_mode = InterruptMode::Stream)
+      .await?;
 
     // Configure device
     self.wait_for_comm_window().await?;
     let config = self.config;
     self.write_config(&config).await?;
 
+    if let Some(retries) = self.verify_retries {
+      let mut attempt = 0;
+      loop {
+        self.wait_for_comm_window().await?;
+        match self.verify_config(&config).await {
+          Err(Error::ConfigMismatch(_)) if attempt < retries => {
+            attempt += 1;
+            self.wait_for_comm_window().await?;
+            self.write_config(&config).await?;
+          }
+          result => break result?,
+        }
+      }
+    }
+
     self.wait_for_comm_window().await?;
     self.ack_reset().await?;
 
@@ -141,17 +362,22 @@ where
     self.wait_for_comm_window().await?;
     self.trigger_autotune().await?;
 
-    loop {
-      self.wait_for_comm_window().await?;
-      if self.info().await?.re_auto_tuning_occurred {
-        break;
-      }
-    }
+    self
+      .poll_info(|info| info.re_auto_tuning_occurred)
+      .await?
+      .ok_or(Error::AutoTuneTimeout)?;
 
     // Set final interrupt mode from config (may switch back to Event Mode)
+    // and only then require explicit ends of the communication windows
     self.wait_for_comm_window().await?;
-    self.set_interrupt_mode(self.config.interrupt_mode).await?;
-
-    Ok(true)
+    let (mode, end_comms) = (self.config.interrupt_mode, self.config.end_comms);
+    self
+      .modify_config_settings(|settings| {
+        settings.interrupt_mode = mode;
+        settings.end_comms = end_comms;
+      })
+      .await?;
+    self.comms = rw::Comms::new(end_comms);
+    Ok(())
   }
 }
@@ -0,0 +1,269 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::{ChargeMode, Error, Info, Iqs7211e};
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Hand the charge mode to the device or force one through manual control,
+  /// and read the [`Info`] of the following window.
+  ///
+  /// The change is written in communication windows forced in Event mode and
+  /// takes effect on the next cycle, which is why the mode is read back from
+  /// the window after. Fails with [`Error::Suspended`] while the
+  /// device is suspended, see [`Iqs7211e::suspend`].
+  pub async fn set_power_control(&mut self, control: PowerControl) -> Result<Info, Error<E>> {
+    if self.suspended.is_some() {
+      return Err(Error::Suspended);
+    }
+
+    self.open_comm_window().await?;
+    match control {
+      PowerControl::Automatic => self.modify_config_settings(|cfg| cfg.manual_control = false).await?,
+      PowerControl::Forced(mode) => {
+        // Select the mode before handing it over so the device never runs a
+        // stale one under manual control
+        self.modify_sys_control(|sys| sys.charge_mode = mode).await?;
+        self.modify_config_settings(|cfg| cfg.manual_control = true).await?;
+      }
+    }
+
+    self.open_comm_window().await?;
+    self.info().await
+  }
+}
+
+/// Who picks the charge mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerControl {
+  /// The device steps down through the modes on its own timeouts, see
+  /// [`crate::Timeouts`].
+  Automatic,
+  /// Manual control holds the device in this mode.
+  Forced(ChargeMode),
+}
+
+/// State of the host system as reported to the [`PowerManager`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemState {
+  Active,
+  /// Screen off, lid closed, USB suspend and the like.
+  Idle,
+}
+
+/// Control applied by the [`PowerManager`] for each [`SystemState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerPolicy {
+  pub active: PowerControl,
+  pub idle: PowerControl,
+}
+
+impl PowerPolicy {
+  pub const fn new(active: PowerControl, idle: PowerControl) -> Self {
+    Self { active, idle }
+  }
+
+  pub const fn with_active(mut self, active: PowerControl) -> Self {
+    self.active = active;
+    self
+  }
+
+  pub const fn with_idle(mut self, idle: PowerControl) -> Self {
+    self.idle = idle;
+    self
+  }
+
+  /// Control applied in `system`.
+  pub const fn control(&self, system: SystemState) -> PowerControl {
+    match system {
+      SystemState::Active => self.active,
+      SystemState::Idle => self.idle,
+    }
+  }
+}
+
+impl Default for PowerPolicy {
+  /// Automatic while the system is in use, held in LP2 while it idles.
+  fn default() -> Self {
+    Self::new(PowerControl::Automatic, PowerControl::Forced(ChargeMode::LowPower2))
+  }
+}
+
+/// Change of the control or of the charge mode reported by the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerTransition {
+  /// Control in effect after the transition.
+  pub control: PowerControl,
+  /// Mode reported before, `None` until the first report was seen.
+  pub from: Option<ChargeMode>,
+  pub to: ChargeMode,
+}
+
+/// Host-driven power policy on top of [`Iqs7211e::set_power_control`].
+///
+/// The system hooks switch between the controls of the [`PowerPolicy`] and
+/// touch the bus only when the control changes. The charge mode is tracked
+/// from the [`Info`] of every report passed to [`PowerManager::observe`], e.g.
+/// the one carried by [`crate::Event::Touch`], so automatic step-downs show up
+/// as transitions too.
+///
+/// The device drops manual control when it resets, so call
+/// [`PowerManager::reapply`] after [`crate::Event::DeviceReset`], a
+/// [`Iqs7211e::hardware_reset`] or [`Iqs7211e::recover`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerManager {
+  policy: PowerPolicy,
+  system: SystemState,
+  control: PowerControl,
+  mode: Option<ChargeMode>,
+}
+
+impl PowerManager {
+  /// Manager for a device fresh out of [`Iqs7211e::initialize`], which picks
+  /// its modes automatically.
+  pub const fn new(policy: PowerPolicy) -> Self {
+    Self { policy, system: SystemState::Active, control: PowerControl::Automatic, mode: None }
+  }
+
+  pub const fn policy(&self) -> PowerPolicy {
+    self.policy
+  }
+
+  pub const fn system(&self) -> SystemState {
+    self.system
+  }
+
+  /// Control last applied to the device.
+  pub const fn control(&self) -> PowerControl {
+    self.control
+  }
+
+  /// Charge mode last reported by the device.
+  pub const fn charge_mode(&self) -> Option<ChargeMode> {
+    self.mode
+  }
+
+  /// Track the charge mode reported in `info`.
+  pub fn observe(&mut self, info: &Info) -> Option<PowerTransition> {
+    let from = self.mode.replace(info.charge_mode);
+    (from != Some(info.charge_mode)).then_some(PowerTransition { control: self.control, from, to: info.charge_mode })
+  }
+
+  /// Apply the idle control of the policy.
+  pub async fn on_system_idle<I, E, RDY, D, RST>(
+    &mut self,
+    device: &mut Iqs7211e<I, RDY, D, RST>,
+  ) -> Result<Option<PowerTransition>, Error<E>>
+  where
+    I: I2c<SevenBitAddress, Error = E>,
+    RDY: Wait,
+    D: DelayNs,
+  {
+    self.enter(device, SystemState::Idle, false).await
+  }
+
+  /// Apply the active control of the policy.
+  pub async fn on_system_active<I, E, RDY, D, RST>(
+    &mut self,
+    device: &mut Iqs7211e<I, RDY, D, RST>,
+  ) -> Result<Option<PowerTransition>, Error<E>>
+  where
+    I: I2c<SevenBitAddress, Error = E>,
+    RDY: Wait,
+    D: DelayNs,
+  {
+    self.enter(device, SystemState::Active, false).await
+  }
+
+  /// Write the control for the current system state again, e.g. after the
+  /// device reset.
+  pub async fn reapply<I, E, RDY, D, RST>(
+    &mut self,
+    device: &mut Iqs7211e<I, RDY, D, RST>,
+  ) -> Result<Option<PowerTransition>, Error<E>>
+  where
+    I: I2c<SevenBitAddress, Error = E>,
+    RDY: Wait,
+    D: DelayNs,
+  {
+    self.enter(device, self.system, true).await
+  }
+
+  async fn enter<I, E, RDY, D, RST>(
+    &mut self,
+    device: &mut Iqs7211e<I, RDY, D, RST>,
+    system: SystemState,
+    force: bool,
+  ) -> Result<Option<PowerTransition>, Error<E>>
+  where
+    I: I2c<SevenBitAddress, Error = E>,
+    RDY: Wait,
+    D: DelayNs,
+  {
+    let control = self.policy.control(system);
+    self.system = system;
+    if control == self.control && !force {
+      return Ok(None);
+    }
+
+    let info = device.set_power_control(control).await?;
+    let changed = control != self.control;
+    self.control = control;
+    let from = self.mode;
+    let transition = self.observe(&info);
+    Ok(match transition {
+      None if changed => Some(PowerTransition { control, from, to: info.charge_mode }),
+      transition => transition,
+    })
+  }
+}
+
+impl Default for PowerManager {
+  fn default() -> Self {
+    Self::new(PowerPolicy::default())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn info(charge_mode: ChargeMode) -> Info {
+    Info {
+      charge_mode,
+      auto_tuning_error: false,
+      re_auto_tuning_occurred: false,
+      alp_auto_tuning_error: false,
+      alp_re_auto_tuning_occurred: false,
+      show_reset: false,
+      num_fingers: 0,
+      trackpad_movement: false,
+      too_many_fingers: false,
+      alp_output: false,
+    }
+  }
+
+  #[test]
+  fn observe_reports_mode_changes_only() {
+    let mut power = PowerManager::default();
+    let first = power.observe(&info(ChargeMode::Active));
+    assert_eq!(first, Some(PowerTransition { control: PowerControl::Automatic, from: None, to: ChargeMode::Active }));
+    assert_eq!(power.observe(&info(ChargeMode::Active)), None);
+
+    let step_down = power.observe(&info(ChargeMode::IdleTouch)).unwrap();
+    assert_eq!((step_down.from, step_down.to), (Some(ChargeMode::Active), ChargeMode::IdleTouch));
+    assert_eq!(power.charge_mode(), Some(ChargeMode::IdleTouch));
+  }
+
+  #[test]
+  fn policy_maps_system_states_to_controls() {
+    let policy = PowerPolicy::default().with_active(PowerControl::Forced(ChargeMode::Active));
+    assert_eq!(policy.control(SystemState::Active), PowerControl::Forced(ChargeMode::Active));
+    assert_eq!(policy.control(SystemState::Idle), PowerControl::Forced(ChargeMode::LowPower2));
+  }
+}
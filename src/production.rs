@@ -0,0 +1,225 @@
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+use crate::frame::{COUNTS, MAX_TRACKPAD_CHANNELS};
+use crate::{ChannelMatrix, Error, Iqs7211e, Pin, Pinout, Pins};
+
+/// Report cycles the Tx test configuration runs before the counts are taken.
+const DEFAULT_SETTLE_CYCLES: u8 = 4;
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
+where
+  I: I2c<SevenBitAddress, Error = E>,
+  RDY: Wait,
+  D: DelayNs,
+{
+  /// Check the sensor for shorted or open Tx/Rx traces, e.g. on an
+  /// end-of-line fixture with nothing on the pad.
+  ///
+  /// Enables the Tx short test configuration (SysControl bit 15), lets it run
+  /// for [`ProductionLimits::settle_cycles`] reports, and reads the count of
+  /// every channel, one row per driven Tx. Normal sensing is restored with a
+  /// trackpad reseed afterwards, also when sampling failed. The verdict is
+  /// [`ProductionReport::evaluate`] applied to those counts. Fails with
+  /// [`Error::Suspended`] while the device is suspended, see
+  /// [`Iqs7211e::suspend`].
+  pub async fn production_test(&mut self, limits: &ProductionLimits) -> Result<ProductionReport, Error<E>> {
+    if self.suspended.is_some() {
+      return Err(Error::Suspended);
+    }
+    let pinout = self.config.pinout;
+    let (rx, tx) = (pinout.rx.len, pinout.tx.len);
+
+    self.open_comm_window().await?;
+    self.modify_sys_control(|sys| sys.tx_test = true).await?;
+    let counts = async {
+      for _ in 0..limits.settle_cycles {
+        self.open_comm_window().await?;
+        self.info().await?;
+      }
+      self.open_comm_window().await?;
+      self.read_ext_page(COUNTS, rx * tx).await
+    }
+    .await;
+
+    // Leave the test configuration whether or not the counts came through
+    self
+      .modify_sys_control(|sys| {
+        sys.tx_test = false;
+        sys.trackpad_reseed = true;
+      })
+      .await?;
+
+    Ok(ProductionReport::evaluate(ChannelMatrix::new(counts?, rx, tx), &pinout, limits))
+  }
+}
+
+/// Inclusive range of acceptable counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CountRange {
+  pub min: u16,
+  pub max: u16,
+}
+
+impl CountRange {
+  pub const fn new(min: u16, max: u16) -> Self {
+    Self { min, max }
+  }
+
+  pub fn contains(&self, count: u16) -> bool {
+    (self.min..=self.max).contains(&count)
+  }
+}
+
+/// Count limits applied by [`Iqs7211e::production_test`].
+///
+/// Every channel starts with the same range; channels near connectors or
+/// board edges can be given their own with [`ProductionLimits::with_channel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProductionLimits {
+  channels: [CountRange; MAX_TRACKPAD_CHANNELS],
+  /// Report cycles to run the Tx test configuration before sampling.
+  pub settle_cycles: u8,
+}
+
+impl ProductionLimits {
+  /// Accept counts within `range` on every channel.
+  pub const fn new(range: CountRange) -> Self {
+    Self { channels: [range; MAX_TRACKPAD_CHANNELS], settle_cycles: DEFAULT_SETTLE_CYCLES }
+  }
+
+  /// Accept counts within `range` on one channel, numbered Tx-major like
+  /// [`crate::ChannelTouchMap::channel`]. Channels past the trackpad are
+  /// ignored.
+  pub const fn with_channel(mut self, channel: usize, range: CountRange) -> Self {
+    if channel < MAX_TRACKPAD_CHANNELS {
+      self.channels[channel] = range;
+    }
+    self
+  }
+
+  pub const fn with_settle_cycles(mut self, settle_cycles: u8) -> Self {
+    self.settle_cycles = settle_cycles;
+    self
+  }
+
+  /// Range accepted on `channel`.
+  pub fn range(&self, channel: usize) -> Option<CountRange> {
+    self.channels.get(channel).copied()
+  }
+}
+
+/// Verdict of [`Iqs7211e::production_test`].
+///
+/// A channel fails when its count lies outside its [`ProductionLimits`]
+/// range. Both electrodes crossing at a failing channel are listed as
+/// failing pins: a shorted or open Tx shows up along its row, an Rx along its
+/// column.
+#[derive(Debug, Clone, Copy)]
+pub struct ProductionReport {
+  /// Counts sampled in the Tx test configuration.
+  pub counts: ChannelMatrix<u16>,
+  failing: u64,
+  rx: Pins,
+  tx: Pins,
+}
+
+impl ProductionReport {
+  /// Judge `counts` laid out by `pinout` against `limits`.
+  pub fn evaluate(counts: ChannelMatrix<u16>, pinout: &Pinout, limits: &ProductionLimits) -> Self {
+    let failing = counts
+      .as_slice()
+      .iter()
+      .enumerate()
+      .filter(|&(channel, &count)| limits.range(channel).is_some_and(|range| !range.contains(count)))
+      .fold(0u64, |failing, (channel, _)| failing | 1 << channel);
+    Self { counts, failing, rx: pinout.rx, tx: pinout.tx }
+  }
+
+  /// Whether every channel lies within its limits.
+  pub fn passed(&self) -> bool {
+    self.failing == 0
+  }
+
+  /// Whether the channel at `(rx, tx)` failed; `false` off the trackpad.
+  pub fn is_failing(&self, rx: usize, tx: usize) -> bool {
+    let rx_count = self.counts.rx_count();
+    rx < rx_count && tx < self.counts.tx_count() && self.failing & (1 << (tx * rx_count + rx)) != 0
+  }
+
+  /// Failing channels as `(rx, tx)` positions, in device order.
+  pub fn failing_channels(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let rx_count = self.counts.rx_count();
+    (0..rx_count * self.counts.tx_count())
+      .filter(|&channel| self.failing & (1 << channel) != 0)
+      .map(move |channel| (channel % rx_count, channel / rx_count))
+  }
+
+  /// Rx pins with at least one failing channel, in [`Pinout`] order.
+  pub fn failing_rx(&self) -> impl Iterator<Item = Pin> + '_ {
+    let tx_count = self.counts.tx_count();
+    self
+      .rx
+      .pins()
+      .iter()
+      .enumerate()
+      .filter(move |&(rx, _)| (0..tx_count).any(|tx| self.is_failing(rx, tx)))
+      .map(|(_, &pin)| pin)
+  }
+
+  /// Tx pins with at least one failing channel, in [`Pinout`] order.
+  pub fn failing_tx(&self) -> impl Iterator<Item = Pin> + '_ {
+    let rx_count = self.counts.rx_count();
+    self
+      .tx
+      .pins()
+      .iter()
+      .enumerate()
+      .filter(move |&(tx, _)| (0..rx_count).any(|rx| self.is_failing(rx, tx)))
+      .map(|(_, &pin)| pin)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn pinout() -> Pinout {
+    Pinout::new([Pin::RxTx0, Pin::RxTx1, Pin::RxTx2], [Pin::Tx8, Pin::Tx9], [], [])
+  }
+
+  fn counts(values: &[u16]) -> ChannelMatrix<u16> {
+    let mut all = [0; MAX_TRACKPAD_CHANNELS];
+    all[..values.len()].copy_from_slice(values);
+    ChannelMatrix::new(all, 3, 2)
+  }
+
+  #[test]
+  fn healthy_sensor_passes() {
+    let limits = ProductionLimits::new(CountRange::new(400, 900));
+    let report = ProductionReport::evaluate(counts(&[500, 510, 520, 530, 540, 550]), &pinout(), &limits);
+    assert!(report.passed());
+    assert_eq!(report.failing_channels().count(), 0);
+    assert_eq!(report.failing_tx().count(), 0);
+  }
+
+  #[test]
+  fn failing_channels_are_attributed_to_their_pins() {
+    let limits = ProductionLimits::new(CountRange::new(400, 900));
+    // Tx9 reads near zero along its whole row; one crossing on Tx8 saturates.
+    let report = ProductionReport::evaluate(counts(&[500, 950, 520, 3, 2, 4]), &pinout(), &limits);
+    assert!(!report.passed());
+    assert!(report.is_failing(1, 0) && !report.is_failing(0, 0));
+    assert!(report.failing_channels().eq([(1, 0), (0, 1), (1, 1), (2, 1)]));
+    assert!(report.failing_tx().eq([Pin::Tx8, Pin::Tx9]));
+    assert!(report.failing_rx().eq([Pin::RxTx0, Pin::RxTx1, Pin::RxTx2]));
+  }
+
+  #[test]
+  fn channel_override_widens_one_channel() {
+    let limits = ProductionLimits::new(CountRange::new(400, 900)).with_channel(1, CountRange::new(400, 1000));
+    let report = ProductionReport::evaluate(counts(&[500, 950, 520, 530, 540, 550]), &pinout(), &limits);
+    assert!(report.passed());
+  }
+}
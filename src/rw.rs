@@ -1,24 +1,131 @@
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::Poll;
+
+use embedded_hal::digital::Error as _;
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
-use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+use embedded_hal_async::i2c::{I2c, Operation, SevenBitAddress};
+
+use crate::{Error, Info, InterruptMode, Iqs7211e, Reg, I2C_ADDR};
 
-use crate::{Error, Iqs7211e, Reg, I2C_ADDR};
+/// Write to 0xFF: requests a window while RDY is high, ends the open one when
+/// the end-comms option is enabled.
+const COMMS_COMMAND: [u8; 2] = [0xFF, 0x00];
 
-impl<I, E, RDY> Iqs7211e<I, RDY>
+/// Communication window bookkeeping.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Comms {
+  /// The device keeps windows open until the host ends them.
+  pub(crate) end_cmd: bool,
+  /// A window is open that only closes on an end command.
+  pub(crate) open: bool,
+  /// A window is open for the next transfer. Without the end-comms option the
+  /// STOP condition of every transfer closes it.
+  pub(crate) window: bool,
+  /// The device runs the staged interrupt mode. Until then, e.g. right after
+  /// a reset, windows are requested since RDY may only pulse on events.
+  pub(crate) synced: bool,
+}
+
+impl Comms {
+  /// State right after the staged ConfigSettings were written with the
+  /// end-comms option set to `end_cmd`: only then did the window survive the
+  /// write.
+  pub(crate) const fn new(end_cmd: bool) -> Self {
+    Self { end_cmd, open: end_cmd, window: end_cmd, synced: true }
+  }
+}
+
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
 where
   I: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
+  D: DelayNs,
 {
   /// Wait for the IQS7211E to open a communication window by asserting RDY low.
   ///
-  /// **Important**: Call this once before a sequence of register operations, not before
-  /// each individual I²C transaction. Multiple reads/writes can occur within a single
-  /// RDY window. The window remains open until the I²C bus is idle (STOP condition).
+  /// The window remains open until the STOP condition ending the next transfer,
+  /// or with the end-comms option until the next wait ends it. Transfers made
+  /// after it closed open a window of their own, so without the option a
+  /// sequence of accesses spans several windows.
   ///
-  /// This matches the pattern used in the official Arduino driver where RDY is checked
-  /// once at the start of higher-level operations (e.g., `queueValueUpdates()`), allowing
-  /// multiple register accesses within that window.
+  /// Gives up with [`Error::RdyTimeout`] after [`crate::Deadlines::comm_window_ms`].
   pub(crate) async fn wait_for_comm_window(&mut self) -> Result<(), Error<E>> {
-    self.rdy.wait_for_low().await.map_err(|_| unreachable!())
+    self.end_comm_window().await?;
+    self.wait_for_rdy().await?;
+    self.comms.open = self.comms.end_cmd;
+    self.comms.window = true;
+    Ok(())
+  }
+
+  async fn wait_for_rdy(&mut self) -> Result<(), Error<E>> {
+    // `NoDelay` would time out at once
+    let Some(timeout_ms) = self.deadlines.comm_window_ms.filter(|_| self.timed) else {
+      return self.rdy.wait_for_low().await.map_err(|e| Error::Pin(e.kind()));
+    };
+
+    let mut rdy = pin!(self.rdy.wait_for_low());
+    let mut timeout = pin!(self.delay.delay_ms(timeout_ms));
+    poll_fn(|cx| {
+      if let Poll::Ready(result) = rdy.as_mut().poll(cx) {
+        return Poll::Ready(result.map_err(|e| Error::Pin(e.kind())));
+      }
+      match timeout.as_mut().poll(cx) {
+        Poll::Ready(()) => Poll::Ready(Err(Error::RdyTimeout)),
+        Poll::Pending => Poll::Pending,
+      }
+    })
+    .await
+  }
+
+  /// Wait for the window that carries the next report.
+  ///
+  /// In Event mode RDY legitimately stays high until the user touches the pad,
+  /// so no deadline applies there.
+  pub(crate) async fn wait_for_event_window(&mut self) -> Result<(), Error<E>> {
+    if !self.event_mode() {
+      return self.wait_for_comm_window().await;
+    }
+    self.end_comm_window().await?;
+    self.rdy.wait_for_low().await.map_err(|e| Error::Pin(e.kind()))?;
+    self.comms.open = self.comms.end_cmd;
+    self.comms.window = true;
+    Ok(())
+  }
+
+  /// Open a communication window for host-initiated accesses.
+  ///
+  /// In Event mode RDY only pulses on activity, so a window is requested
+  /// explicitly instead of waited for.
+  pub(crate) async fn open_comm_window(&mut self) -> Result<(), Error<E>> {
+    if self.event_mode() {
+      self.force_comms_request().await
+    } else {
+      self.wait_for_comm_window().await
+    }
+  }
+
+  /// Whether RDY only pulses on events: in Event mode, while suspended (see
+  /// [`Iqs7211e::suspend`]), and as far as the driver knows before the staged
+  /// mode is in place.
+  fn event_mode(&self) -> bool {
+    self.suspended.is_some() || !self.comms.synced || matches!(self.config.interrupt_mode, InterruptMode::Event)
+  }
+
+  /// Read [`Info`] once per communication window until `done` holds.
+  ///
+  /// Returns `None` once [`crate::Deadlines::max_polls`] windows went by
+  /// without `done` holding.
+  pub(crate) async fn poll_info(&mut self, done: impl Fn(&Info) -> bool) -> Result<Option<Info>, Error<E>> {
+    for _ in 0..self.deadlines.max_polls {
+      self.wait_for_comm_window().await?;
+      let info = self.info().await?;
+      if done(&info) {
+        return Ok(Some(info));
+      }
+    }
+    Ok(None)
   }
 
   /// Force a communication request when RDY is HIGH (per datasheet 11.9.2).
@@ -28,13 +135,42 @@ where
   /// communication window. Required during initialization or when polling
   /// is necessary while in Event Mode.
   pub(crate) async fn force_comms_request(&mut self) -> Result<(), Error<E>> {
+    // A window held open for an end command would swallow the request
+    self.end_comm_window().await?;
     // Write 0x00 to address 0xFF (without waiting for RDY first)
-    let buf = [0xFFu8, 0x00];
-    self.i2c.write(I2C_ADDR, &buf).await.map_err(Error::I2c)?;
+    self.i2c.write(I2C_ADDR, &COMMS_COMMAND).await.map_err(Error::I2c)?;
     // Now wait for the RDY window that the device will open
     self.wait_for_comm_window().await
   }
 
+  /// End the communication window if the device holds it open for an end
+  /// command (see [`crate::Config::end_comms`]). Otherwise the STOP condition
+  /// of the last transfer closed it already and nothing is sent.
+  pub(crate) async fn end_comm_window(&mut self) -> Result<(), Error<E>> {
+    if !self.comms.open {
+      return Ok(());
+    }
+    self.i2c.write(I2C_ADDR, &COMMS_COMMAND).await.map_err(Error::I2c)?;
+    self.comms.open = false;
+    self.comms.window = false;
+    Ok(())
+  }
+
+  /// Open a window for the next transfer unless one still is.
+  async fn ensure_comm_window(&mut self) -> Result<(), Error<E>> {
+    if self.comms.window {
+      return Ok(());
+    }
+    self.open_comm_window().await
+  }
+
+  /// Track the STOP condition of a transfer, which closes the window unless
+  /// the device waits for an end command.
+  fn transferred<T>(&mut self, result: Result<T, E>) -> Result<T, Error<E>> {
+    self.comms.window = self.comms.open;
+    result.map_err(Error::I2c)
+  }
+
   // Typed helpers
   pub(crate) async fn read<const N: usize, T: TryFrom<[u8; N]>>(&mut self, reg: Reg) -> Result<T, Error<E>> {
     let mut b = [0u8; N];
@@ -52,9 +188,12 @@ where
     self.write_bytes(reg, &b).await
   }
 
+  /// Read registers from `reg` on, in the open window or in a new one.
   pub(crate) async fn read_bytes(&mut self, reg: Reg, buf: &mut [u8]) -> Result<(), Error<E>> {
+    self.ensure_comm_window().await?;
     let addr = [reg as u8];
-    self.i2c.write_read(I2C_ADDR, &addr, buf).await.map_err(Error::I2c)
+    let result = self.i2c.write_read(I2C_ADDR, &addr, buf).await;
+    self.transferred(result)
   }
 
   pub(crate) async fn write_bytes(&mut self, reg: Reg, data: &[u8]) -> Result<(), Error<E>> {
@@ -65,18 +204,36 @@ where
     let mut buf = [0u8; 32];
     buf[0] = reg.into();
     buf[1..=len].copy_from_slice(data);
-    self.i2c.write(I2C_ADDR, &buf[..=len]).await.map_err(Error::I2c)
+    self.ensure_comm_window().await?;
+    let result = self.i2c.write(I2C_ADDR, &buf[..=len]).await;
+    self.transferred(result)
   }
 
   // Extended (16-bit addressed) reads for diagnostic pages
   pub(crate) async fn read_ext_bytes(&mut self, addr: u16, buf: &mut [u8]) -> Result<(), Error<E>> {
+    self.ensure_comm_window().await?;
     let regs = addr.to_be_bytes();
-    self.i2c.write_read(I2C_ADDR, &regs, buf).await.map_err(Error::I2c)
+    let result = self.i2c.write_read(I2C_ADDR, &regs, buf).await;
+    self.transferred(result)
   }
 
-  pub(crate) async fn read_u16_ext(&mut self, addr: u16) -> Result<u16, Error<E>> {
-    let mut buf = [0u8; 2];
-    self.read_ext_bytes(addr, &mut buf).await?;
-    Ok(u16::from_le_bytes(buf))
+  /// Extended reads of four pages in one transfer, with a repeated start
+  /// before each, so all of them come from the same window.
+  pub(crate) async fn read_ext_pages(&mut self, pages: [(u16, &mut [u8]); 4]) -> Result<(), Error<E>> {
+    self.ensure_comm_window().await?;
+    let [(a, buf_a), (b, buf_b), (c, buf_c), (d, buf_d)] = pages;
+    let regs = [a.to_be_bytes(), b.to_be_bytes(), c.to_be_bytes(), d.to_be_bytes()];
+    let mut operations = [
+      Operation::Write(&regs[0]),
+      Operation::Read(buf_a),
+      Operation::Write(&regs[1]),
+      Operation::Read(buf_b),
+      Operation::Write(&regs[2]),
+      Operation::Read(buf_c),
+      Operation::Write(&regs[3]),
+      Operation::Read(buf_d),
+    ];
+    let result = self.i2c.transaction(I2C_ADDR, &mut operations).await;
+    self.transferred(result)
   }
 }
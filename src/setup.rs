@@ -1,9 +1,11 @@
+use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 use embedded_hal_async::i2c::{I2c, SevenBitAddress};
 
-use crate::{AlpHardware, AutoProxCycles, ChargeMode, Error, Info, InterruptMode, Iqs7211e, Reg};
-
-const MAX_TRACKPAD_CHANNELS: usize = 42;
+use crate::{
+  AlpHardware, AutoProxCycles, ChannelTouchMap, ChargeMode, Error, Info, InterruptMode, Iqs7211e, NoDelay, NoReset,
+  Reg, DELTAS, MAX_TRACKPAD_CHANNELS, REFERENCES,
+};
 
 /// Snapshot of the live measurements that are typically reviewed while tuning
 /// a new hardware design.
@@ -17,6 +19,9 @@ pub struct SetupSnapshot {
   /// Flattened view into the trackpad base targets (0xE100). Use
   /// `rx_count * tx_count` to determine how many entries are populated.
   pub trackpad_base_targets: [u16; MAX_TRACKPAD_CHANNELS],
+  /// Channels in touch, indexed like the flattened arrays above. A channel
+  /// that stays in touch without a finger points at a stuck electrode.
+  pub touch_map: ChannelTouchMap,
   /// Number of Rx electrodes captured in the snapshot.
   pub rx_count: usize,
   /// Number of Tx electrodes captured in the snapshot.
@@ -35,18 +40,19 @@ pub struct SetupSnapshot {
 
 /// State machine helper that guides the operator through the manual setup
 /// described in the Azoteq reference documentation.
-pub struct SetupSession<'a, I, RDY> {
-  device: &'a mut Iqs7211e<I, RDY>,
+pub struct SetupSession<'a, I, RDY, D = NoDelay, RST = NoReset> {
+  device: &'a mut Iqs7211e<I, RDY, D, RST>,
   original_interrupt_mode: InterruptMode,
   original_lp1_auto_prox_cycles: AutoProxCycles,
   original_lp2_auto_prox_cycles: AutoProxCycles,
   manual_control_enabled: bool,
 }
 
-impl<I, E, RDY> Iqs7211e<I, RDY>
+impl<I, E, RDY, D, RST> Iqs7211e<I, RDY, D, RST>
 where
   I: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
+  D: DelayNs,
 {
   /// Begin an interactive setup sequence.
   ///
@@ -54,7 +60,7 @@ where
   /// measurements can be collected and presented to the user. When the session
   /// is finished, call [`SetupSession::finish`] to leave the device in a clean
   /// state.
-  pub fn begin_setup(&mut self) -> SetupSession<'_, I, RDY> {
+  pub fn begin_setup(&mut self) -> SetupSession<'_, I, RDY, D, RST> {
     SetupSession {
       device: self,
       // Defaults are placeholders; real values are captured during initialize()
@@ -66,10 +72,11 @@ where
   }
 }
 
-impl<'a, I, E, RDY> SetupSession<'a, I, RDY>
+impl<'a, I, E, RDY, D, RST> SetupSession<'a, I, RDY, D, RST>
 where
   I: I2c<SevenBitAddress, Error = E>,
   RDY: Wait,
+  D: DelayNs,
 {
   /// Perform the one-time initialisation required before tuning.
   ///
@@ -118,8 +125,9 @@ where
     debug_assert!(populated <= MAX_TRACKPAD_CHANNELS);
 
     let info_flags = self.device.info().await?;
-    let base = self.read_measurement_block(0xE100, populated).await?;
-    let deltas = self.read_measurement_block(0xE200, populated).await?;
+    let touch_map = self.device.channel_touch_map().await?;
+    let base = self.device.read_ext_page(REFERENCES, populated).await?;
+    let deltas = self.device.read_ext_page(DELTAS, populated).await?;
     let alp_channel_lta = self.device.read_u16(Reg::LowPowerChannelLta).await?;
     let alp_channel_count = self.device.read_u16(Reg::LowPowerChannelCount).await?;
     let alp_count_a = self.device.read_u16(Reg::LowPowerChannelCountA).await?;
@@ -131,6 +139,7 @@ where
       info: info_flags,
       trackpad_deltas: deltas,
       trackpad_base_targets: base,
+      touch_map,
       rx_count,
       tx_count,
       alp_channel_lta,
@@ -157,19 +166,4 @@ where
     self.device.write(Reg::AlpHardware, alp).await?;
     Ok(())
   }
-
-  async fn read_measurement_block(
-    &mut self,
-    from: u16,
-    populated: usize,
-  ) -> Result<[u16; MAX_TRACKPAD_CHANNELS], Error<E>> {
-    let mut out = [0u16; MAX_TRACKPAD_CHANNELS];
-
-    for (idx, entry) in out.iter_mut().take(populated).enumerate() {
-      let addr = from + idx as u16;
-      *entry = self.device.read_u16_ext(addr).await?;
-    }
-
-    Ok(out)
-  }
 }
@@ -0,0 +1,895 @@
+//! Register-accurate model of the IQS7211E for host-side testing.
+//!
+//! [`Simulator`] implements the I²C and RDY traits consumed by
+//! [`Iqs7211e`](crate::Iqs7211e) (both the async and the blocking
+//! `embedded-hal` flavours) on top of an in-memory copy of the register map:
+//!
+//! - the 8-bit addressed registers from `reg.rs`, with auto-increment across
+//!   16-bit words and read-only protection of the status block (0x00–0x1E)
+//! - the 16-bit addressed diagnostic pages at 0xE000 (counts), 0xE100
+//!   (references), 0xE200 (deltas) and 0xE300 (ATI compensation)
+//! - the SHOW_RESET/ACK_RESET handshake, SW_RESET, MCLR and re-ATI completion
+//!   flags, suspend, and the counts page of the Tx short test
+//! - RDY windows: every cycle in Stream mode, only on enabled events or a
+//!   forced communication request (write to 0xFF) in Event mode; a window
+//!   closes with the transaction, or with the end-comms option on the next
+//!   write to 0xFF. Anything but a request is not acknowledged outside one
+//!
+//! Touches and gestures are scripted as [`Report`]s, each applied on one
+//! report cycle; the relative movement registers follow finger 1 between
+//! consecutive reports. Time only advances while the driver waits for RDY.
+//!
+//! ```no_run
+//! use iqs7211e::sim::{self, Report, Simulator};
+//! use iqs7211e::{Config, Event, Iqs7211e};
+//!
+//! let device = Simulator::new();
+//! let (i2c, rdy) = device.split();
+//! let mut controller = Iqs7211e::new(i2c, rdy, Config::default());
+//! sim::block_on(controller.initialize()).unwrap();
+//!
+//! device.push(Report::touch(120, 340));
+//! let event = sim::block_on(controller.next_event()).unwrap();
+//! assert!(matches!(event, Event::Touch(..)));
+//! ```
+
+use core::cell::RefCell;
+use core::convert::Infallible;
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll};
+
+use embedded_hal::digital;
+use embedded_hal::i2c::{self, ErrorKind, NoAcknowledgeSource, Operation, SevenBitAddress};
+
+use crate::{Gesture, InterruptMode, Point, Reg, SwipeDirection, TapCount, Touchpoint, I2C_ADDR, PRODUCT_NUMBER};
+
+#[cfg(test)]
+mod tests;
+
+const WORDS: usize = 0x80;
+const READ_ONLY_END: usize = Reg::AlpAutoTuningCompA as usize;
+const COMMS_REQUEST: u8 = 0xFF;
+const EXT_PAGE_SIZE: usize = 0x100;
+const EXT_FIRST: usize = 0xE000;
+const EXT_PAGES: usize = 4;
+const EXT_REFERENCES: u16 = 0xE100;
+const EXT_DELTAS: u16 = 0xE200;
+const CHANNELS: usize = 42;
+const QUEUE_LEN: usize = 32;
+
+/// Report cycles spent booting after a reset before the first RDY window.
+const BOOT_CYCLES: u8 = 2;
+/// Report cycles an ATI routine takes to complete.
+const ATI_CYCLES: u8 = 4;
+/// Cycles simulated while waiting for an Event-mode window before RDY is
+/// considered stuck high.
+const MAX_IDLE_CYCLES: u32 = 4096;
+
+// SysControl (0x33)
+const CHARGE_MODE_MASK: u16 = 0b111;
+const TP_RESEED: u16 = 1 << 3;
+const ALP_RESEED: u16 = 1 << 4;
+const TP_RETUNE: u16 = 1 << 5;
+const ALP_RETUNE: u16 = 1 << 6;
+const ACK_RESET: u16 = 1 << 7;
+const SW_RESET: u16 = 1 << 9;
+const SUSPEND: u16 = 1 << 11;
+const TX_TEST: u16 = 1 << 15;
+
+// ConfigSettings (0x34)
+const END_COMMS: u16 = 1 << 6;
+const MANUAL_CONTROL: u16 = 1 << 7;
+const EVENT_MODE: u16 = 1 << 8;
+const GESTURE_EVENT: u16 = 1 << 9;
+const TRACKPAD_EVENT: u16 = 1 << 10;
+const RETUNE_EVENT: u16 = 1 << 11;
+const ALP_EVENT: u16 = 1 << 13;
+/// Power-on ConfigSettings: re-ATI and watchdog enabled, Event mode with
+/// gesture and trackpad events.
+const CONFIG_SETTINGS_RESET: u16 = 0x072C;
+
+// InfoFlags (0x0F)
+const RE_ATI: u16 = 1 << 4;
+const ALP_RE_ATI: u16 = 1 << 6;
+const SHOW_RESET: u16 = 1 << 7;
+const NUM_FINGERS_SHIFT: u16 = 8;
+const NUM_FINGERS_MASK: u16 = 0b11 << NUM_FINGERS_SHIFT;
+const TP_MOVEMENT: u16 = 1 << 10;
+const ALP_OUTPUT: u16 = 1 << 14;
+
+const NO_CONTACT: u16 = 0xFFFF;
+
+/// Firmware identity reported by a freshly reset simulator.
+const APP_MAJOR: u8 = 1;
+const APP_MINOR: u8 = 0;
+const APP_COMMIT: u32 = 0x0000_1A2B;
+const ROM_NUMBER: u16 = 0x0002;
+const ROM_MAJOR: u8 = 1;
+const ROM_MINOR: u8 = 2;
+const ROM_COMMIT: u32 = 0x0000_0C3D;
+
+/// One report cycle worth of scripted sensor activity.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Report {
+  /// Finger 1 and finger 2 slots. `None` reads back as an empty slot.
+  pub contacts: [Option<Touchpoint>; 2],
+  /// Gesture flagged during this cycle.
+  pub gesture: Option<Gesture>,
+  /// ALP channel output during this cycle.
+  pub proximity: bool,
+}
+
+impl Report {
+  /// A single finger resting at `(x, y)`.
+  pub fn touch(x: u16, y: u16) -> Self {
+    Self::contact(Touchpoint { point: Point::new(x, y), strength: 100, area: 4 })
+  }
+
+  /// A single finger with explicit strength and area.
+  pub fn contact(primary: Touchpoint) -> Self {
+    Self { contacts: [Some(primary), None], ..Self::default() }
+  }
+
+  /// Two fingers on the pad.
+  pub fn contacts(primary: Touchpoint, secondary: Touchpoint) -> Self {
+    Self { contacts: [Some(primary), Some(secondary)], ..Self::default() }
+  }
+
+  /// All fingers lifted.
+  pub fn release() -> Self {
+    Self::default()
+  }
+
+  /// A gesture reported while no finger is on the pad.
+  pub fn gesture(gesture: Gesture) -> Self {
+    Self { gesture: Some(gesture), ..Self::default() }
+  }
+
+  /// Flag a gesture in addition to the contacts of this cycle.
+  pub fn with_gesture(mut self, gesture: Gesture) -> Self {
+    self.gesture = Some(gesture);
+    self
+  }
+
+  /// Set the ALP output for this cycle, e.g. a hand hovering over the pad.
+  pub fn with_proximity(mut self, proximity: bool) -> Self {
+    self.proximity = proximity;
+    self
+  }
+}
+
+/// Simulated IQS7211E.
+///
+/// Owns the device state; hand the peripherals returned by
+/// [`Simulator::split`] to the driver and keep the simulator around to script
+/// activity and inspect registers.
+pub struct Simulator {
+  device: RefCell<Device>,
+}
+
+impl Simulator {
+  /// Power up a simulated device. It boots in Event mode with SHOW_RESET set.
+  pub fn new() -> Self {
+    Self { device: RefCell::new(Device::new()) }
+  }
+
+  /// Borrow the I²C bus and RDY line of the device.
+  pub fn split(&self) -> (SimI2c<'_>, SimRdy<'_>) {
+    (SimI2c { device: &self.device }, SimRdy { device: &self.device })
+  }
+
+  /// Borrow the MCLR input of the device.
+  pub fn reset_pin(&self) -> SimReset<'_> {
+    SimReset { device: &self.device }
+  }
+
+  /// Queue a report to be applied on a future cycle.
+  ///
+  /// Reports are consumed one per cycle. Once the queue drains the contacts
+  /// of the last report stay on the pad.
+  ///
+  /// # Panics
+  /// Panics if more than 32 reports are pending.
+  pub fn push(&self, report: Report) {
+    self.device.borrow_mut().queue.push(report);
+  }
+
+  /// Cut and restore power, as if the chip browned out.
+  pub fn power_cycle(&self) {
+    self.device.borrow_mut().reset();
+  }
+
+  /// Read a 16-bit register by its 8-bit address.
+  pub fn register(&self, address: u8) -> u16 {
+    self.device.borrow().word(address as usize)
+  }
+
+  /// Overwrite a register by its 8-bit address, bypassing read-only
+  /// protection and side effects.
+  pub fn set_register(&self, address: u8, value: u16) {
+    self.device.borrow_mut().set_word(address as usize, value);
+  }
+
+  /// Load the per-channel counters returned by the 0xE100 and 0xE200 pages.
+  pub fn set_trackpad_counts(&self, base_targets: &[u16], deltas: &[u16]) {
+    self.set_ext_page(EXT_REFERENCES, base_targets);
+    self.set_ext_page(EXT_DELTAS, deltas);
+  }
+
+  /// Load the per-channel values returned by the extended page at `address`:
+  /// 0xE000 (counts), 0xE100 (references), 0xE200 (deltas) or 0xE300 (ATI
+  /// compensation).
+  ///
+  /// # Panics
+  /// Panics if `address` is not one of these pages.
+  pub fn set_ext_page(&self, address: u16, values: &[u16]) {
+    let page = (address as usize)
+      .checked_sub(EXT_FIRST)
+      .map(|offset| offset / EXT_PAGE_SIZE)
+      .filter(|&page| page < EXT_PAGES && address as usize % EXT_PAGE_SIZE == 0)
+      .expect("not an extended page");
+    let mut device = self.device.borrow_mut();
+    for (slot, &value) in device.ext_pages[page].iter_mut().zip(values) {
+      *slot = value;
+    }
+  }
+
+  /// Load the per-channel counts the 0xE000 page returns while the Tx short
+  /// test configuration is enabled.
+  pub fn set_tx_test_counts(&self, counts: &[u16]) {
+    let mut device = self.device.borrow_mut();
+    for (slot, &value) in device.tx_test_counts.iter_mut().zip(counts) {
+      *slot = value;
+    }
+  }
+
+  /// Delivery mode currently selected in ConfigSettings.
+  pub fn interrupt_mode(&self) -> InterruptMode {
+    match self.device.borrow().word(Reg::ConfigSettings as usize) & EVENT_MODE {
+      0 => InterruptMode::Stream,
+      _ => InterruptMode::Event,
+    }
+  }
+
+  /// Whether a communication window is currently open (RDY low).
+  pub fn window_open(&self) -> bool {
+    self.device.borrow().window
+  }
+
+  /// Number of report cycles simulated so far.
+  pub fn cycles(&self) -> u32 {
+    self.device.borrow().cycles
+  }
+
+  /// Number of resets (power-on, SW_RESET) the device went through.
+  pub fn resets(&self) -> u32 {
+    self.device.borrow().resets
+  }
+
+  /// Number of trackpad and ALP ATI routines started by the host.
+  pub fn auto_tunes(&self) -> (u32, u32) {
+    let device = self.device.borrow();
+    (device.trackpad_tunes, device.alp_tunes)
+  }
+
+  /// Number of trackpad and ALP reseeds requested by the host.
+  pub fn reseeds(&self) -> (u32, u32) {
+    let device = self.device.borrow();
+    (device.trackpad_reseeds, device.alp_reseeds)
+  }
+
+  /// Number of I²C transactions addressed to the device.
+  pub fn transactions(&self) -> u32 {
+    self.device.borrow().transactions
+  }
+
+  /// NACK the next `count` transactions.
+  pub fn fail_next(&self, count: u8) {
+    self.device.borrow_mut().failures = count;
+  }
+
+  /// NACK the next `count` reads of the extended memory, as with a glitch
+  /// during a long burst.
+  pub fn fail_ext_reads(&self, count: u8) {
+    self.device.borrow_mut().ext_failures = count;
+  }
+
+  /// Hold RDY high regardless of the device state, as with a broken trace.
+  pub fn set_rdy_stuck(&self, stuck: bool) {
+    self.device.borrow_mut().rdy_stuck = stuck;
+  }
+
+  /// Latch the device up: it NACKs every transaction and keeps RDY high until
+  /// MCLR is pulsed or power is cycled.
+  pub fn latch_up(&self) {
+    self.device.borrow_mut().latched = true;
+  }
+
+  /// Flip the lowest bit of the next `count` writes starting at register
+  /// `address`, as with a glitch on SDA.
+  pub fn corrupt_writes(&self, address: u8, count: u8) {
+    self.device.borrow_mut().corrupt = (address, count);
+  }
+
+  /// Keep ATI routines running forever, as with a sensor that cannot reach
+  /// its targets.
+  pub fn stall_auto_tune(&self, stall: bool) {
+    self.device.borrow_mut().ati_stalled = stall;
+  }
+
+  /// Let ALP ATI routines take `cycles` report cycles instead of as many as
+  /// the trackpad ones, so their completion flags show up on other cycles.
+  pub fn set_alp_auto_tune_cycles(&self, cycles: u8) {
+    self.device.borrow_mut().alp_ati_cycles = cycles.max(1);
+  }
+
+  /// Delay provider that completes immediately and records the requested time.
+  pub fn delay(&self) -> SimDelay<'_> {
+    SimDelay { device: &self.device }
+  }
+
+  /// Total time requested from [`SimDelay`]s, in nanoseconds.
+  pub fn elapsed_ns(&self) -> u64 {
+    self.device.borrow().elapsed_ns
+  }
+}
+
+impl Default for Simulator {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// I²C bus attached to a [`Simulator`].
+pub struct SimI2c<'a> {
+  device: &'a RefCell<Device>,
+}
+
+impl i2c::ErrorType for SimI2c<'_> {
+  type Error = ErrorKind;
+}
+
+impl embedded_hal_async::i2c::I2c<SevenBitAddress> for SimI2c<'_> {
+  async fn transaction(&mut self, address: SevenBitAddress, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    self.device.borrow_mut().transfer(address, operations)
+  }
+}
+
+impl i2c::I2c<SevenBitAddress> for SimI2c<'_> {
+  fn transaction(&mut self, address: SevenBitAddress, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    self.device.borrow_mut().transfer(address, operations)
+  }
+}
+
+/// RDY line attached to a [`Simulator`].
+///
+/// Waiting for a low level advances the simulation until the device opens a
+/// communication window. If it never would (Event mode with nothing
+/// scheduled, or [`Simulator::set_rdy_stuck`]), the wait never completes.
+pub struct SimRdy<'a> {
+  device: &'a RefCell<Device>,
+}
+
+impl digital::ErrorType for SimRdy<'_> {
+  type Error = Infallible;
+}
+
+impl embedded_hal_async::digital::Wait for SimRdy<'_> {
+  async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+    Ok(())
+  }
+
+  async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+    if !self.device.borrow_mut().open_window() {
+      core::future::pending::<()>().await;
+    }
+    Ok(())
+  }
+
+  async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+    Ok(())
+  }
+
+  async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+    self.wait_for_low().await
+  }
+
+  async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+    self.wait_for_low().await
+  }
+}
+
+impl digital::InputPin for SimRdy<'_> {
+  fn is_high(&mut self) -> Result<bool, Infallible> {
+    Ok(!self.device.borrow_mut().open_window())
+  }
+
+  fn is_low(&mut self) -> Result<bool, Infallible> {
+    Ok(self.device.borrow_mut().open_window())
+  }
+}
+
+/// MCLR line attached to a [`Simulator`].
+///
+/// Driving it low holds the device in reset; releasing it reboots the device.
+pub struct SimReset<'a> {
+  device: &'a RefCell<Device>,
+}
+
+impl digital::ErrorType for SimReset<'_> {
+  type Error = Infallible;
+}
+
+impl digital::OutputPin for SimReset<'_> {
+  fn set_low(&mut self) -> Result<(), Infallible> {
+    self.device.borrow_mut().mclr_low = true;
+    Ok(())
+  }
+
+  fn set_high(&mut self) -> Result<(), Infallible> {
+    let mut device = self.device.borrow_mut();
+    if core::mem::take(&mut device.mclr_low) {
+      device.reset();
+    }
+    Ok(())
+  }
+}
+
+/// Delay provider attached to a [`Simulator`].
+pub struct SimDelay<'a> {
+  device: &'a RefCell<Device>,
+}
+
+impl embedded_hal_async::delay::DelayNs for SimDelay<'_> {
+  async fn delay_ns(&mut self, ns: u32) {
+    self.device.borrow_mut().elapsed_ns += u64::from(ns);
+  }
+}
+
+impl embedded_hal::delay::DelayNs for SimDelay<'_> {
+  fn delay_ns(&mut self, ns: u32) {
+    self.device.borrow_mut().elapsed_ns += u64::from(ns);
+  }
+}
+
+/// Poll a driver future against a [`Simulator`] until it completes.
+///
+/// The simulator never yields, so a future that is not ready after one poll
+/// is waiting for a communication window that will never open.
+///
+/// # Panics
+/// Panics if the future stalls.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+  let mut future = pin!(future);
+  let waker = crate::waker::noop_waker();
+  match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+    Poll::Ready(output) => output,
+    Poll::Pending => panic!("simulated IQS7211E never opened a communication window"),
+  }
+}
+
+struct Queue {
+  reports: [Report; QUEUE_LEN],
+  head: usize,
+  len: usize,
+}
+
+impl Queue {
+  fn push(&mut self, report: Report) {
+    assert!(self.len < QUEUE_LEN, "simulator report queue is full");
+    self.reports[(self.head + self.len) % QUEUE_LEN] = report;
+    self.len += 1;
+  }
+
+  fn pop(&mut self) -> Option<Report> {
+    if self.len == 0 {
+      return None;
+    }
+    let report = self.reports[self.head];
+    self.head = (self.head + 1) % QUEUE_LEN;
+    self.len -= 1;
+    Some(report)
+  }
+}
+
+enum Pointer {
+  Reg(usize),
+  Ext(u16),
+}
+
+struct Device {
+  mem: [u8; WORDS * 2],
+  ext_pages: [[u16; CHANNELS]; EXT_PAGES],
+  tx_test_counts: [u16; CHANNELS],
+  queue: Queue,
+  contacts: [Option<Touchpoint>; 2],
+  window: bool,
+  comms_request: bool,
+  reset_pending: bool,
+  booting: u8,
+  ati: u8,
+  alp_ati: u8,
+  alp_ati_cycles: u8,
+  cycles: u32,
+  resets: u32,
+  trackpad_tunes: u32,
+  alp_tunes: u32,
+  trackpad_reseeds: u32,
+  alp_reseeds: u32,
+  transactions: u32,
+  failures: u8,
+  ext_failures: u8,
+  rdy_stuck: bool,
+  latched: bool,
+  mclr_low: bool,
+  corrupt: (u8, u8),
+  ati_stalled: bool,
+  elapsed_ns: u64,
+}
+
+impl Device {
+  fn new() -> Self {
+    let mut device = Self {
+      mem: [0; WORDS * 2],
+      ext_pages: [[0; CHANNELS]; EXT_PAGES],
+      tx_test_counts: [0; CHANNELS],
+      queue: Queue { reports: [Report::default(); QUEUE_LEN], head: 0, len: 0 },
+      contacts: [None, None],
+      window: false,
+      comms_request: false,
+      reset_pending: false,
+      booting: 0,
+      ati: 0,
+      alp_ati: 0,
+      alp_ati_cycles: ATI_CYCLES,
+      cycles: 0,
+      resets: 0,
+      trackpad_tunes: 0,
+      alp_tunes: 0,
+      trackpad_reseeds: 0,
+      alp_reseeds: 0,
+      transactions: 0,
+      failures: 0,
+      ext_failures: 0,
+      rdy_stuck: false,
+      latched: false,
+      mclr_low: false,
+      corrupt: (0, 0),
+      ati_stalled: false,
+      elapsed_ns: 0,
+    };
+    device.reset();
+    device
+  }
+
+  fn word(&self, address: usize) -> u16 {
+    match address < WORDS {
+      true => u16::from_le_bytes([self.mem[address * 2], self.mem[address * 2 + 1]]),
+      false => 0,
+    }
+  }
+
+  fn set_word(&mut self, address: usize, value: u16) {
+    if address < WORDS {
+      self.mem[address * 2..address * 2 + 2].copy_from_slice(&value.to_le_bytes());
+    }
+  }
+
+  fn update(&mut self, reg: Reg, f: impl FnOnce(u16) -> u16) {
+    let value = f(self.word(reg as usize));
+    self.set_word(reg as usize, value);
+  }
+
+  fn set_bits(&mut self, reg: Reg, bits: u16, enable: bool) {
+    self.update(reg, |v| if enable { v | bits } else { v & !bits });
+  }
+
+  fn has_bits(&self, reg: Reg, bits: u16) -> bool {
+    self.word(reg as usize) & bits == bits
+  }
+
+  /// Restore power-on register values and start booting.
+  fn reset(&mut self) {
+    self.mem = [0; WORDS * 2];
+    self.set_version(Reg::AppVersion, PRODUCT_NUMBER, APP_MAJOR, APP_MINOR, APP_COMMIT);
+    self.set_version(Reg::RomVersion, ROM_NUMBER, ROM_MAJOR, ROM_MINOR, ROM_COMMIT);
+    self.set_word(Reg::ConfigSettings as usize, CONFIG_SETTINGS_RESET);
+    self.set_word(Reg::InfoFlags as usize, SHOW_RESET);
+    self.contacts = [None, None];
+    self.write_contacts();
+    self.window = false;
+    self.comms_request = false;
+    self.reset_pending = true;
+    self.booting = BOOT_CYCLES;
+    self.ati = 0;
+    self.alp_ati = 0;
+    self.latched = false;
+    self.resets += 1;
+  }
+
+  fn set_version(&mut self, reg: Reg, number: u16, major: u8, minor: u8, commit: u32) {
+    let words = [number, major as u16, minor as u16, commit as u16, (commit >> 16) as u16];
+    for (offset, word) in words.into_iter().enumerate() {
+      self.set_word(reg as usize + offset, word);
+    }
+  }
+
+  fn transfer(&mut self, address: SevenBitAddress, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+    if address != I2C_ADDR {
+      return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+    }
+    self.transactions += 1;
+    if self.latched || self.mclr_low {
+      return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+    }
+    if self.failures > 0 {
+      self.failures -= 1;
+      return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data));
+    }
+    // Outside a window only a communication request is acknowledged
+    if !self.window && !matches!(operations, [Operation::Write([COMMS_REQUEST, ..])]) {
+      return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+    }
+    let ext = |operation: &Operation<'_>| matches!(operation, Operation::Write([hi, _]) if *hi >= 0xE0);
+    if self.ext_failures > 0 && operations.iter().any(ext) {
+      self.ext_failures -= 1;
+      return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data));
+    }
+
+    let mut pointer = None;
+    for operation in operations.iter_mut() {
+      match operation {
+        Operation::Write(bytes) => pointer = self.write(bytes),
+        Operation::Read(buf) => match pointer {
+          Some(Pointer::Reg(start)) => self.read(start, buf),
+          Some(Pointer::Ext(start)) => self.read_ext(start, buf),
+          None => self.read(0, buf),
+        },
+      }
+    }
+
+    // The window closes with the STOP condition ending the transaction,
+    // unless the device waits for an end command.
+    if !self.has_bits(Reg::ConfigSettings, END_COMMS) {
+      self.window = false;
+    }
+    Ok(())
+  }
+
+  /// Handle a write and return the address pointer it leaves behind.
+  fn write(&mut self, bytes: &[u8]) -> Option<Pointer> {
+    match *bytes {
+      [] => None,
+      [reg] => Some(Pointer::Reg(reg as usize)),
+      [COMMS_REQUEST, ..] if self.window && self.has_bits(Reg::ConfigSettings, END_COMMS) => {
+        self.window = false;
+        None
+      }
+      [COMMS_REQUEST, ..] => {
+        self.comms_request = true;
+        None
+      }
+      [hi, lo] if hi >= 0xE0 => Some(Pointer::Ext(u16::from_be_bytes([hi, lo]))),
+      [reg, ref data @ ..] => {
+        let start = reg as usize * 2;
+        for (offset, &byte) in data.iter().enumerate() {
+          let index = start + offset;
+          if index >= READ_ONLY_END * 2 && index < self.mem.len() {
+            self.mem[index] = byte;
+          }
+        }
+        if self.corrupt.0 == reg && self.corrupt.1 > 0 && start < self.mem.len() {
+          self.corrupt.1 -= 1;
+          self.mem[start] ^= 0x01;
+        }
+        let end = start + data.len();
+        let sys_control = Reg::SysControl as usize * 2;
+        if start <= sys_control + 1 && end > sys_control {
+          self.apply_sys_control();
+        }
+        None
+      }
+    }
+  }
+
+  fn read(&self, start: usize, buf: &mut [u8]) {
+    for (offset, byte) in buf.iter_mut().enumerate() {
+      *byte = self.mem.get(start * 2 + offset).copied().unwrap_or(0);
+    }
+  }
+
+  fn read_ext(&self, start: u16, buf: &mut [u8]) {
+    for (offset, chunk) in buf.chunks_mut(2).enumerate() {
+      let address = start as usize + offset;
+      let page = address.wrapping_sub(EXT_FIRST) / EXT_PAGE_SIZE;
+      let index = address % EXT_PAGE_SIZE;
+      let values = match page {
+        0 if self.has_bits(Reg::SysControl, TX_TEST) => Some(&self.tx_test_counts),
+        _ => self.ext_pages.get(page),
+      };
+      let value = values.and_then(|values| values.get(index)).copied().unwrap_or(0);
+      let bytes = value.to_le_bytes();
+      chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+  }
+
+  /// Act on the self-clearing command bits of SysControl.
+  fn apply_sys_control(&mut self) {
+    let control = self.word(Reg::SysControl as usize);
+    if control & SW_RESET != 0 {
+      self.reset();
+      return;
+    }
+    if control & ACK_RESET != 0 {
+      self.set_bits(Reg::InfoFlags, SHOW_RESET, false);
+    }
+    if control & TP_RETUNE != 0 {
+      self.ati = ATI_CYCLES;
+      self.trackpad_tunes += 1;
+    }
+    if control & ALP_RETUNE != 0 {
+      self.alp_ati = self.alp_ati_cycles;
+      self.alp_tunes += 1;
+    }
+    if control & TP_RESEED != 0 {
+      self.trackpad_reseeds += 1;
+    }
+    if control & ALP_RESEED != 0 {
+      self.alp_reseeds += 1;
+    }
+    self.set_bits(Reg::SysControl, ACK_RESET | TP_RETUNE | ALP_RETUNE | TP_RESEED | ALP_RESEED, false);
+  }
+
+  fn event_mode(&self) -> bool {
+    self.has_bits(Reg::ConfigSettings, EVENT_MODE)
+  }
+
+  fn suspended(&self) -> bool {
+    self.has_bits(Reg::SysControl, SUSPEND)
+  }
+
+  /// Whether anything is scheduled that could eventually raise an event.
+  fn busy(&self) -> bool {
+    self.booting > 0 || self.reset_pending || self.ati > 0 || self.alp_ati > 0 || self.queue.len > 0
+  }
+
+  /// Advance the simulation until RDY goes low. Returns `false` if it never
+  /// would.
+  fn open_window(&mut self) -> bool {
+    if self.rdy_stuck || self.latched || self.mclr_low {
+      return false;
+    }
+    if self.window {
+      return true;
+    }
+    for _ in 0..MAX_IDLE_CYCLES {
+      let event = self.step();
+      let running = self.booting == 0;
+      let streaming = !self.event_mode() && !self.suspended();
+      if running && (self.comms_request || streaming || event) {
+        self.comms_request = false;
+        self.window = true;
+        return true;
+      }
+      if !self.busy() && !self.comms_request {
+        return false;
+      }
+    }
+    false
+  }
+
+  /// Simulate one report cycle. Returns `true` if an enabled event occurred.
+  fn step(&mut self) -> bool {
+    self.cycles += 1;
+    self.set_bits(Reg::InfoFlags, RE_ATI | ALP_RE_ATI | TP_MOVEMENT, false);
+    for reg in [
+      Reg::RelativeX,
+      Reg::RelativeY,
+      Reg::GestureX,
+      Reg::GestureY,
+      Reg::Gestures,
+    ] {
+      self.set_word(reg as usize, 0);
+    }
+
+    if self.booting > 0 {
+      self.booting -= 1;
+      return false;
+    }
+    if self.suspended() {
+      return false;
+    }
+
+    let settings = self.word(Reg::ConfigSettings as usize);
+    let mut event = core::mem::take(&mut self.reset_pending);
+
+    if self.ati > 0 && !self.ati_stalled {
+      self.ati -= 1;
+      if self.ati == 0 {
+        self.set_bits(Reg::InfoFlags, RE_ATI, true);
+        event |= settings & RETUNE_EVENT != 0;
+      }
+    }
+    if self.alp_ati > 0 && !self.ati_stalled {
+      self.alp_ati -= 1;
+      if self.alp_ati == 0 {
+        self.set_bits(Reg::InfoFlags, ALP_RE_ATI, true);
+        event |= settings & RETUNE_EVENT != 0;
+      }
+    }
+
+    if let Some(report) = self.queue.pop() {
+      let moved = report.contacts != self.contacts;
+      if let (Some(from), Some(to)) = (self.contacts[0], report.contacts[0]) {
+        self.set_word(Reg::RelativeX as usize, to.point.x.wrapping_sub(from.point.x));
+        self.set_word(Reg::RelativeY as usize, to.point.y.wrapping_sub(from.point.y));
+      }
+      self.contacts = report.contacts;
+      self.write_contacts();
+      self.set_bits(Reg::InfoFlags, TP_MOVEMENT, moved);
+      event |= moved && settings & TRACKPAD_EVENT != 0;
+
+      if let Some(gesture) = report.gesture {
+        let (x, y, bits) = encode_gesture(&gesture);
+        self.set_word(Reg::GestureX as usize, x);
+        self.set_word(Reg::GestureY as usize, y);
+        self.set_word(Reg::Gestures as usize, bits);
+        event |= settings & GESTURE_EVENT != 0;
+      }
+
+      let proximity = self.word(Reg::InfoFlags as usize) & ALP_OUTPUT != 0;
+      self.set_bits(Reg::InfoFlags, ALP_OUTPUT, report.proximity);
+      event |= report.proximity != proximity && settings & ALP_EVENT != 0;
+    }
+
+    if settings & MANUAL_CONTROL != 0 {
+      let mode = self.word(Reg::SysControl as usize) & CHARGE_MODE_MASK;
+      self.update(Reg::InfoFlags, |v| (v & !CHARGE_MODE_MASK) | mode);
+    }
+
+    event
+  }
+
+  fn write_contacts(&mut self) {
+    let slots = [Reg::Finger1X as usize, Reg::Finger2X as usize];
+    let mut fingers = 0;
+    for (slot, contact) in slots.into_iter().zip(self.contacts) {
+      let words = match contact {
+        Some(tp) => {
+          fingers += 1;
+          [tp.point.x, tp.point.y, tp.strength, tp.area]
+        }
+        None => [NO_CONTACT, NO_CONTACT, 0, 0],
+      };
+      for (offset, value) in words.into_iter().enumerate() {
+        self.set_word(slot + offset, value);
+      }
+    }
+    self.update(Reg::InfoFlags, |v| (v & !NUM_FINGERS_MASK) | (fingers << NUM_FINGERS_SHIFT));
+  }
+}
+
+/// Inverse of the `Gesture` decoding: gesture X, gesture Y and flag word.
+fn encode_gesture(gesture: &Gesture) -> (u16, u16, u16) {
+  let swipe_bit = |direction: &SwipeDirection| match direction {
+    SwipeDirection::XPositive => 0,
+    SwipeDirection::XNegative => 1,
+    SwipeDirection::YPositive => 2,
+    SwipeDirection::YNegative => 3,
+  };
+  match gesture {
+    Gesture::Tap(count, p) => {
+      let bit = match count {
+        TapCount::One => 0,
+        TapCount::Two => 1,
+        TapCount::Three => 2,
+      };
+      (p.x, p.y, 1 << bit)
+    }
+    Gesture::PressHold(p) => (p.x, p.y, 1 << 3),
+    Gesture::Palm => (0, 0, 1 << 4),
+    Gesture::Swipe(direction, v) => (v.dx as u16, v.dy as u16, 1 << (8 + swipe_bit(direction))),
+    Gesture::SwipeHold(direction, v) => (v.dx as u16, v.dy as u16, 1 << (12 + swipe_bit(direction))),
+  }
+}
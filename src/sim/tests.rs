@@ -0,0 +1,992 @@
+use super::*;
+use crate::config::test_config as config;
+use crate::{
+  Axes, AxesInset, Calibration, ChannelMatrix, ChargeMode, Config, ConfigBlock, ConfigMismatch, CountRange, Deadlines,
+  Error, Event, EventFilter, EventTriggers, FirmwareAction, FirmwarePolicy, Gestures, Iqs7211e, MainOscillator,
+  MaxTouches, OtherSettings, Pin, Pinout, PowerControl, PowerManager, ProductionLimits, Recalibration, Recovery,
+  Release, ReleaseRange, Resolution, SettingsVersion, Suspend, Swipe, SystemState, Tap, Trackpad, Vector,
+};
+
+// InfoFlags (0x0F) bits the simulator never raises on its own.
+const AUTO_TUNE_ERROR: u16 = 1 << 3;
+const TOO_MANY_FINGERS: u16 = 1 << 12;
+// ConfigSettings (0x34) bit the simulator does not act on.
+const WATCHDOG: u16 = 1 << 5;
+
+fn tp(x: u16, y: u16) -> Touchpoint {
+  Touchpoint { point: Point::new(x, y), strength: 80, area: 3 }
+}
+
+fn ready(device: &Simulator) -> Iqs7211e<SimI2c<'_>, SimRdy<'_>> {
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config());
+  assert!(block_on(controller.initialize()).unwrap());
+  controller
+}
+
+fn streaming(device: &Simulator) -> Iqs7211e<SimI2c<'_>, SimRdy<'_>> {
+  let (i2c, rdy) = device.split();
+  let config = config().with_interrupt_mode(InterruptMode::Stream);
+  let mut controller = Iqs7211e::new(i2c, rdy, config).with_event_filter(EventFilter::all());
+  assert!(block_on(controller.initialize()).unwrap());
+  controller
+}
+
+#[test]
+fn initialize_acknowledges_reset_and_pushes_config() {
+  let device = Simulator::new();
+  let _controller = ready(&device);
+
+  assert_eq!(device.register(Reg::InfoFlags as u8) & SHOW_RESET, 0);
+  assert_eq!(device.resets(), 1);
+  assert!(matches!(device.interrupt_mode(), InterruptMode::Event));
+
+  let cfg = config();
+  let mapping = cfg.pinout.mapping();
+  assert_eq!(device.register(Reg::RxTxMapping0_1 as u8), u16::from_le_bytes([mapping[0], mapping[1]]));
+  let gestures: [u8; 22] = cfg.gestures.try_into().unwrap();
+  assert_eq!(device.register(Reg::GestureEnable as u8), u16::from_le_bytes([gestures[0], gestures[1]]));
+  let cycles = cfg.pinout.cycles();
+  assert_eq!(device.register(Reg::ProxACycle20 as u8), u16::from_le_bytes([cycles[60], cycles[61]]));
+}
+
+#[test]
+fn initialize_requests_software_reset_without_show_reset() {
+  let device = Simulator::new();
+  device.set_register(Reg::InfoFlags as u8, 0);
+
+  let _controller = ready(&device);
+  assert_eq!(device.resets(), 2);
+  assert_eq!(device.register(Reg::InfoFlags as u8) & SHOW_RESET, 0);
+}
+
+#[test]
+fn initialize_rejects_foreign_product_number() {
+  let device = Simulator::new();
+  device.set_register(Reg::AppVersion as u8, 0x1234);
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config());
+
+  assert!(matches!(block_on(controller.initialize()), Err(Error::InvalidChipId(0x34))));
+}
+
+#[test]
+fn device_identity_reads_app_and_rom_versions() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+
+  let identity = block_on(controller.device_identity()).unwrap();
+  assert_eq!(identity.product, PRODUCT_NUMBER);
+  assert_eq!((identity.app.major, identity.app.minor, identity.app.commit), (APP_MAJOR, APP_MINOR, APP_COMMIT));
+  assert_eq!((identity.rom.number, identity.rom.major, identity.rom.minor), (ROM_NUMBER, ROM_MAJOR, ROM_MINOR));
+  assert_eq!(identity.rom.commit, ROM_COMMIT);
+  assert_eq!(controller.identity(), Some(&identity));
+}
+
+#[test]
+fn initialize_rejects_firmware_outside_policy() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let policy = FirmwarePolicy::new(ReleaseRange::new(Release::new(1, 1), Release::new(1, 9)));
+  let mut controller = Iqs7211e::new(i2c, rdy, config()).with_firmware_policy(policy);
+
+  match block_on(controller.initialize()) {
+    Err(Error::UnsupportedFirmware(identity)) => assert_eq!(Release::from(identity.app), Release::new(1, 0)),
+    other => panic!("unexpected {other:?}"),
+  }
+  // Nothing was written past the version check.
+  assert_eq!(device.register(Reg::InfoFlags as u8) & SHOW_RESET, SHOW_RESET);
+}
+
+#[test]
+fn initialize_refuses_invalid_config_before_touching_the_bus() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let mut config = config();
+  config.conversion_frequency.trackpad.period = 128;
+  let mut controller = Iqs7211e::new(i2c, rdy, config);
+
+  match block_on(controller.initialize()) {
+    Err(Error::InvalidConfig(issue)) => assert_eq!(issue.field, "conversion_frequency.trackpad.period"),
+    other => panic!("unexpected {other:?}"),
+  }
+  assert_eq!(device.transactions(), 0);
+}
+
+#[test]
+fn initialize_warns_about_firmware_outside_policy() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let policy = FirmwarePolicy::new(ReleaseRange::ANY)
+    .with_rom(ReleaseRange::new(Release::new(2, 0), Release::new(2, 0)))
+    .with_action(FirmwareAction::Warn);
+  let mut controller = Iqs7211e::new(i2c, rdy, config()).with_firmware_policy(policy);
+
+  assert!(block_on(controller.initialize()).unwrap());
+  assert!(!controller.firmware_supported());
+}
+
+#[test]
+fn initialize_keeps_settings_of_warm_device_with_matching_stamp() {
+  let device = Simulator::new();
+  let staged = config().with_settings_version(SettingsVersion::Hash);
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, staged);
+  assert!(block_on(controller.initialize()).unwrap());
+  assert_eq!(device.register(Reg::SettingsVersion as u8), staged.settings_stamp().unwrap());
+  let tunes = device.auto_tunes();
+  let transactions = device.transactions();
+
+  // The host wakes from deep sleep and brings a fresh driver up.
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, staged);
+  assert!(!block_on(controller.initialize()).unwrap());
+  assert_eq!(device.resets(), 1);
+  assert_eq!(device.auto_tunes(), tunes);
+  assert!(device.transactions() - transactions <= 8);
+}
+
+#[test]
+fn initialize_wakes_warm_device_left_suspended() {
+  for mode in [Suspend::Deep, Suspend::WakeOnProximity] {
+    let device = Simulator::new();
+    let staged = config()
+      .with_interrupt_mode(InterruptMode::Stream)
+      .with_settings_version(SettingsVersion::Hash);
+    let (i2c, rdy) = device.split();
+    let mut controller = Iqs7211e::new(i2c, rdy, staged);
+    assert!(block_on(controller.initialize()).unwrap());
+    let settings = device.register(Reg::ConfigSettings as u8);
+    block_on(controller.suspend(mode)).unwrap();
+
+    // The host reboots without resuming the device first.
+    let (i2c, rdy) = device.split();
+    let mut controller = Iqs7211e::new(i2c, rdy, staged);
+    assert!(!block_on(controller.initialize()).unwrap());
+    assert_eq!(device.resets(), 1);
+    assert_eq!(device.register(Reg::SysControl as u8) & SUSPEND, 0);
+    assert_eq!(device.register(Reg::ConfigSettings as u8), settings);
+    device.push(Report::touch(42, 24));
+    assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+  }
+}
+
+#[test]
+fn initialize_reconfigures_warm_device_with_other_stamp() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config().with_settings_version(SettingsVersion::Fixed(0x0102)));
+  assert!(block_on(controller.initialize()).unwrap());
+
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config().with_settings_version(SettingsVersion::Fixed(0x0103)));
+  assert!(block_on(controller.initialize()).unwrap());
+  assert_eq!(device.resets(), 2);
+  assert_eq!(device.register(Reg::SettingsVersion as u8), 0x0103);
+}
+
+#[test]
+fn transfers_outside_a_window_are_not_acknowledged() {
+  let device = Simulator::new();
+  let _controller = ready(&device);
+  assert!(!device.window_open());
+
+  let (mut i2c, _) = device.split();
+  let mut info = [0; 2];
+  let result = i2c::I2c::write_read(&mut i2c, I2C_ADDR, &[Reg::InfoFlags as u8], &mut info);
+  assert_eq!(result, Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)));
+  // Only the request for a window gets through.
+  i2c::I2c::write(&mut i2c, I2C_ADDR, &[COMMS_REQUEST, 0x00]).unwrap();
+}
+
+#[test]
+fn initialize_surfaces_bus_errors() {
+  let device = Simulator::new();
+  device.fail_next(1);
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config());
+
+  assert!(matches!(block_on(controller.initialize()), Err(Error::I2c(ErrorKind::NoAcknowledge(_)))));
+}
+
+#[test]
+fn initialize_times_out_when_rdy_is_stuck() {
+  let device = Simulator::new();
+  device.set_rdy_stuck(true);
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config())
+    .with_delay(device.delay())
+    .with_deadlines(Deadlines::new(Some(10), 20));
+
+  assert!(matches!(block_on(controller.initialize()), Err(Error::RdyTimeout)));
+  assert_eq!(device.elapsed_ns(), 10_000_000);
+}
+
+#[test]
+fn initialize_gives_up_on_stalled_auto_tune() {
+  let device = Simulator::new();
+  device.stall_auto_tune(true);
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config())
+    .with_delay(device.delay())
+    .with_deadlines(Deadlines::new(Some(10), 20));
+
+  assert!(matches!(block_on(controller.initialize()), Err(Error::AutoTuneTimeout)));
+  assert_eq!(device.elapsed_ns(), 0);
+}
+
+#[test]
+fn verify_config_accepts_pushed_config() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  block_on(controller.verify_config(&config())).unwrap();
+}
+
+#[test]
+fn verify_config_names_corrupted_register() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let expected = device.register(Reg::Cycle11 as u8);
+  device.set_register(Reg::Cycle11 as u8, expected ^ 0x0100);
+
+  let mismatch =
+    ConfigMismatch { block: ConfigBlock::Cycles, address: Reg::Cycle11 as u8, expected, actual: expected ^ 0x0100 };
+  assert!(matches!(block_on(controller.verify_config(&config())), Err(Error::ConfigMismatch(m)) if m == mismatch));
+}
+
+#[test]
+fn verify_config_checks_config_settings_but_not_modes() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let settings = device.register(Reg::ConfigSettings as u8);
+  // Manual control and the delivery mode are the driver's to switch.
+  device.set_register(Reg::ConfigSettings as u8, settings ^ MANUAL_CONTROL ^ EVENT_MODE);
+  block_on(controller.verify_config(&config())).unwrap();
+
+  // The watchdog is staged, so losing it is a mismatch.
+  device.set_register(Reg::ConfigSettings as u8, settings ^ (1 << 5));
+  assert!(matches!(
+    block_on(controller.verify_config(&config())),
+    Err(Error::ConfigMismatch(ConfigMismatch { block: ConfigBlock::ConfigSettings, address: 0x34, .. }))
+  ));
+}
+
+#[test]
+fn set_interrupt_mode_switches_to_event_at_runtime() {
+  let device = Simulator::new();
+  let mut controller = streaming(&device);
+  block_on(controller.set_interrupt_mode(InterruptMode::Event)).unwrap();
+  assert!(matches!(controller.config.interrupt_mode, InterruptMode::Event));
+
+  // RDY stays high without activity now, so the read has to force its window.
+  let settings = block_on(controller.config_settings()).unwrap();
+  assert!(matches!(settings.interrupt_mode, InterruptMode::Event));
+}
+
+#[test]
+fn initialize_rewrites_config_after_mismatch() {
+  let device = Simulator::new();
+  device.corrupt_writes(Reg::GestureEnable as u8, 1);
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config()).with_config_verification(2);
+
+  assert!(block_on(controller.initialize()).unwrap());
+  block_on(controller.verify_config(&config())).unwrap();
+}
+
+#[test]
+fn initialize_reports_persistent_mismatch() {
+  let device = Simulator::new();
+  device.corrupt_writes(Reg::TpRxSettings as u8, 3);
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config()).with_config_verification(1);
+
+  assert!(matches!(
+    block_on(controller.initialize()),
+    Err(Error::ConfigMismatch(ConfigMismatch { block: ConfigBlock::Trackpad, address: 0x41, .. }))
+  ));
+}
+
+#[test]
+fn read_config_decodes_live_registers() {
+  let pinout = Pinout::new(
+    [Pin::RxTx0, Pin::RxTx2, Pin::RxTx4, Pin::RxTx6],
+    [Pin::Tx8, Pin::Tx9, Pin::Tx10],
+    [Pin::RxTx2, Pin::RxTx4],
+    [Pin::Tx8, Pin::Tx10],
+  );
+  let trackpad = Trackpad::new()
+    .with_axes(Axes::new(false, false, true), Resolution::new(800, 600), AxesInset::default())
+    .single_touch();
+  let staged = Config::default()
+    .with_pinout(pinout)
+    .with_trackpad(trackpad)
+    .with_interrupt_mode(InterruptMode::Stream);
+
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, staged);
+  assert!(block_on(controller.initialize()).unwrap());
+  // Swap XY, dynamic IIR and MAV filters, as in the vendor reference settings.
+  assert_eq!(device.register(Reg::TpRxSettings as u8) & 0xFF, 0x2C);
+
+  let live = block_on(controller.read_config()).unwrap();
+  assert!(live.trackpad.axes.swap_axes);
+  assert!(matches!(live.trackpad.max_simultaneous_touches, MaxTouches::One));
+  assert_eq!((live.trackpad.resolution.x, live.trackpad.resolution.y), (800, 600));
+  assert!(matches!(live.interrupt_mode, InterruptMode::Stream));
+  assert_eq!(live.pinout.mapping(), staged.pinout.mapping());
+  assert_eq!(live.pinout.cycles(), staged.pinout.cycles());
+  assert_eq!((live.pinout.alp_rx.len, live.pinout.alp_tx.len), (2, 2));
+  block_on(controller.verify_config(&live)).unwrap();
+}
+
+#[test]
+fn read_config_keeps_custom_cycle_allocation() {
+  let pinout = config().pinout;
+  let mut cycles = [0u8; 63];
+  cycles.copy_from_slice(&pinout.cycles()[..63]);
+  // Leave the first channel unsensed, as GUI exports for odd-shaped pads do.
+  cycles[1] = 0xFF;
+
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let staged = config().with_pinout(pinout.with_cycles(cycles));
+  let mut controller = Iqs7211e::new(i2c, rdy, staged);
+  assert!(block_on(controller.initialize()).unwrap());
+
+  let live = block_on(controller.read_config()).unwrap();
+  assert_eq!(live.pinout.cycles(), staged.pinout.cycles());
+  block_on(controller.verify_config(&live)).unwrap();
+}
+
+#[test]
+fn apply_gestures_rewrites_window_without_retune() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let tunes = device.auto_tunes();
+
+  let gestures = Gestures::default()
+    .enable_tap(Tap::all())
+    .enable_swipe(Swipe::horizontal());
+  block_on(controller.apply_gestures(gestures)).unwrap();
+
+  let encoded: [u8; 22] = gestures.try_into().unwrap();
+  assert_eq!(device.register(Reg::GestureEnable as u8), u16::from_le_bytes([encoded[0], encoded[1]]));
+  assert_eq!(device.auto_tunes(), tunes);
+  let staged: [u8; 22] = controller.config().gestures.try_into().unwrap();
+  assert_eq!(staged, encoded);
+  let staged = controller.config;
+  block_on(controller.verify_config(&staged)).unwrap();
+}
+
+#[test]
+fn apply_skips_unchanged_blocks() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let transactions = device.transactions();
+
+  let trackpad = controller.config().trackpad;
+  block_on(controller.apply_trackpad(trackpad)).unwrap();
+  block_on(controller.apply_timing(controller.config.timing)).unwrap();
+  assert_eq!(device.transactions(), transactions);
+}
+
+#[test]
+fn apply_hardware_retunes_only_changed_engine() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let (trackpad_tunes, alp_tunes) = device.auto_tunes();
+
+  let mut hardware = controller.config().hardware;
+  hardware.alp.rf_filter = !hardware.alp.rf_filter;
+  block_on(controller.apply_hardware(hardware)).unwrap();
+  assert_eq!(device.auto_tunes(), (trackpad_tunes, alp_tunes + 1));
+
+  hardware.trackpad.rf_filter = !hardware.trackpad.rf_filter;
+  block_on(controller.apply_hardware(hardware)).unwrap();
+  assert_eq!(device.auto_tunes(), (trackpad_tunes + 1, alp_tunes + 1));
+  let staged = controller.config;
+  block_on(controller.verify_config(&staged)).unwrap();
+}
+
+#[test]
+fn apply_auto_tune_keeps_alp_compensation_found_by_ati() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let (trackpad_tunes, alp_tunes) = device.auto_tunes();
+  let compensation = 0x0123;
+  device.set_register(Reg::AlpAutoTuningCompA as u8, compensation);
+
+  let mut auto_tune = controller.config().auto_tune;
+  auto_tune.retune_threshold += 10;
+  auto_tune.alp_compensation.engine_a += 1;
+  block_on(controller.apply_auto_tune(auto_tune)).unwrap();
+  assert_eq!(device.register(Reg::AlpAutoTuningCompA as u8), compensation);
+  assert_eq!(device.auto_tunes(), (trackpad_tunes, alp_tunes));
+
+  auto_tune.tune.target += 10;
+  block_on(controller.apply_auto_tune(auto_tune)).unwrap();
+  assert_eq!(device.register(Reg::AlpAutoTuningCompA as u8), compensation);
+  assert_eq!(device.auto_tunes(), (trackpad_tunes + 1, alp_tunes));
+}
+
+#[test]
+fn other_settings_are_pushed_and_applied_with_retune() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let staged = config().with_other_settings(OtherSettings::new(MainOscillator::Megahertz18, 2));
+  let mut controller = Iqs7211e::new(i2c, rdy, staged);
+  assert!(block_on(controller.initialize()).unwrap());
+  assert_eq!(device.register(Reg::OtherSettings as u8), 0b1_0010);
+  let (trackpad_tunes, alp_tunes) = device.auto_tunes();
+
+  let adjusted = OtherSettings::default().with_main_osc_adjust(15);
+  block_on(controller.apply_other_settings(adjusted)).unwrap();
+  assert_eq!(block_on(controller.other_settings()).unwrap(), adjusted);
+  assert_eq!(device.auto_tunes(), (trackpad_tunes + 1, alp_tunes + 1));
+  assert_eq!(block_on(controller.read_config()).unwrap().other_settings, adjusted);
+}
+
+#[test]
+fn hardware_reset_restores_configuration() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config())
+    .with_delay(device.delay())
+    .with_reset(device.reset_pin());
+  assert!(block_on(controller.initialize()).unwrap());
+  let mapping = device.register(Reg::RxTxMapping0_1 as u8);
+  device.set_register(Reg::RxTxMapping0_1 as u8, 0);
+
+  block_on(controller.hardware_reset()).unwrap();
+  assert_eq!(device.resets(), 2);
+  assert_eq!(device.register(Reg::RxTxMapping0_1 as u8), mapping);
+  assert_eq!(device.register(Reg::InfoFlags as u8) & SHOW_RESET, 0);
+}
+
+#[test]
+fn hardware_reset_requires_reset_line() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  assert!(matches!(block_on(controller.hardware_reset()), Err(Error::NoResetPin)));
+}
+
+#[test]
+fn hardware_reset_and_recover_require_delay() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config()).with_reset(device.reset_pin());
+  assert!(block_on(controller.initialize()).unwrap());
+
+  assert!(matches!(block_on(controller.hardware_reset()), Err(Error::NoDelay)));
+  assert!(matches!(block_on(controller.recover()), Err(Error::NoDelay)));
+  assert_eq!(device.resets(), 1);
+}
+
+#[test]
+fn recover_resumes_responsive_device() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  assert_eq!(block_on(controller.recover()).unwrap(), Recovery::Resumed);
+  assert_eq!(device.resets(), 1);
+}
+
+#[test]
+fn recover_reconfigures_after_brown_out() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  device.power_cycle();
+
+  assert_eq!(block_on(controller.recover()).unwrap(), Recovery::Reconfigured);
+  assert_eq!(device.register(Reg::InfoFlags as u8) & SHOW_RESET, 0);
+  assert!(matches!(device.interrupt_mode(), InterruptMode::Event));
+}
+
+#[test]
+fn recover_escalates_to_hardware_reset() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config())
+    .with_delay(device.delay())
+    .with_deadlines(Deadlines::new(Some(10), 20))
+    .with_reset(device.reset_pin());
+  assert!(block_on(controller.initialize()).unwrap());
+  device.latch_up();
+
+  assert_eq!(block_on(controller.recover()).unwrap(), Recovery::HardwareReset);
+  assert_eq!(device.resets(), 2);
+  assert_eq!(device.register(Reg::InfoFlags as u8) & SHOW_RESET, 0);
+}
+
+#[test]
+fn recover_without_reset_line_reports_last_error() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  device.latch_up();
+
+  assert!(matches!(block_on(controller.recover()), Err(Error::I2c(ErrorKind::NoAcknowledge(_)))));
+}
+
+#[test]
+fn next_event_reports_single_touch() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+
+  device.push(Report::contact(tp(120, 340)));
+  match block_on(controller.next_event()).unwrap() {
+    Event::Touch(primary, info) => {
+      assert_eq!(primary, tp(120, 340));
+      assert_eq!(info.num_fingers, 1);
+    }
+    other => panic!("unexpected {other:?}"),
+  }
+}
+
+#[test]
+fn next_event_reports_multi_touch() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+
+  device.push(Report::contacts(tp(10, 20), tp(500, 600)));
+  match block_on(controller.next_event()).unwrap() {
+    Event::MultiTouch(primary, secondary, info) => {
+      assert_eq!(primary, tp(10, 20));
+      assert_eq!(secondary, tp(500, 600));
+      assert_eq!(info.num_fingers, 2);
+    }
+    other => panic!("unexpected {other:?}"),
+  }
+}
+
+#[test]
+fn next_event_decodes_gestures() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+
+  device.push(Report::gesture(Gesture::Tap(TapCount::Two, Point::new(300, 400))));
+  device.push(Report::gesture(Gesture::Swipe(SwipeDirection::YNegative, Vector::new(-12, 250))));
+
+  let first = block_on(controller.next_event()).unwrap();
+  assert!(matches!(first, Event::Gesture(Gesture::Tap(TapCount::Two, p), _) if p == Point::new(300, 400)));
+  let second = block_on(controller.next_event()).unwrap();
+  assert!(
+    matches!(second, Event::Gesture(Gesture::Swipe(SwipeDirection::YNegative, v), _) if v == Vector::new(-12, 250))
+  );
+}
+
+#[test]
+fn next_event_skips_windows_without_contacts() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  block_on(controller.set_interrupt_mode(InterruptMode::Stream)).unwrap();
+
+  device.push(Report::release());
+  device.push(Report::release());
+  device.push(Report::touch(1, 2));
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+}
+
+#[test]
+fn next_event_reports_release_after_touch() {
+  let device = Simulator::new();
+  let mut controller = streaming(&device);
+
+  device.push(Report::touch(1, 2));
+  device.push(Report::release());
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+  assert_eq!(block_on(controller.next_event()).unwrap(), Event::Release);
+}
+
+#[test]
+fn next_event_reports_relative_motion() {
+  let device = Simulator::new();
+  let mut controller = streaming(&device);
+
+  device.push(Report::touch(100, 100));
+  device.push(Report::touch(110, 95));
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+  assert_eq!(block_on(controller.next_event()).unwrap(), Event::Motion(Vector::new(10, -5)));
+  assert_eq!(block_on(controller.relative_motion()).unwrap(), Vector::new(10, -5));
+}
+
+#[test]
+fn next_event_reports_proximity_edges() {
+  let device = Simulator::new();
+  let mut controller = streaming(&device);
+
+  device.push(Report::release().with_proximity(true));
+  device.push(Report::release().with_proximity(true));
+  device.push(Report::release());
+  assert_eq!(block_on(controller.next_event()).unwrap(), Event::Proximity(true));
+  assert_eq!(block_on(controller.next_event()).unwrap(), Event::Proximity(false));
+}
+
+#[test]
+fn next_event_reports_completed_retune() {
+  let device = Simulator::new();
+  let mut controller = streaming(&device);
+
+  let mut hardware = controller.config().hardware;
+  hardware.trackpad.rf_filter = !hardware.trackpad.rf_filter;
+  block_on(controller.apply_hardware(hardware)).unwrap();
+  assert_eq!(block_on(controller.next_event()).unwrap(), Event::Retuned);
+}
+
+#[test]
+fn next_event_reports_flag_edges_before_contacts() {
+  let device = Simulator::new();
+  let mut controller = streaming(&device);
+  let info = device.register(Reg::InfoFlags as u8);
+  device.set_register(Reg::InfoFlags as u8, info | AUTO_TUNE_ERROR | TOO_MANY_FINGERS);
+
+  device.push(Report::touch(1, 2));
+  device.push(Report::touch(3, 4));
+  assert_eq!(block_on(controller.next_event()).unwrap(), Event::AutoTuneError);
+  assert_eq!(block_on(controller.next_event()).unwrap(), Event::TooManyFingers);
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+  // Flags still raised on the next report are not repeated.
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+}
+
+#[test]
+fn next_event_reconfigures_after_unexpected_reset() {
+  let device = Simulator::new();
+  let mut controller = streaming(&device);
+  let mapping = device.register(Reg::RxTxMapping0_1 as u8);
+  device.power_cycle();
+
+  assert_eq!(block_on(controller.next_event()).unwrap(), Event::DeviceReset);
+  assert_eq!(device.resets(), 2);
+  assert_eq!(device.register(Reg::RxTxMapping0_1 as u8), mapping);
+  assert_eq!(device.register(Reg::InfoFlags as u8) & SHOW_RESET, 0);
+  assert!(matches!(device.interrupt_mode(), InterruptMode::Stream));
+}
+
+#[test]
+fn event_filter_suppresses_state_changes() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let config = config().with_interrupt_mode(InterruptMode::Stream);
+  let mut controller = Iqs7211e::new(i2c, rdy, config).with_event_filter(EventFilter::none());
+  assert!(block_on(controller.initialize()).unwrap());
+
+  device.push(Report::touch(1, 2));
+  device.push(Report::release().with_proximity(true));
+  device.push(Report::touch(5, 6));
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(p, _) if p.point == Point::new(5, 6)));
+}
+
+#[test]
+fn state_changes_are_opt_in() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config().with_interrupt_mode(InterruptMode::Stream));
+  assert!(block_on(controller.initialize()).unwrap());
+
+  device.push(Report::touch(1, 2));
+  device.push(Report::touch(11, 2));
+  device.push(Report::release().with_proximity(true));
+  device.push(Report::touch(5, 6));
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(p, _) if p.point == Point::new(11, 2)));
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(p, _) if p.point == Point::new(5, 6)));
+}
+
+#[test]
+#[should_panic(expected = "never opened a communication window")]
+fn event_mode_without_activity_keeps_rdy_high() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let _ = block_on(controller.next_event());
+}
+
+#[test]
+fn setup_snapshot_reads_extended_pages() {
+  let device = Simulator::new();
+  let base: [u16; 12] = core::array::from_fn(|i| 300 + i as u16);
+  let deltas: [u16; 12] = core::array::from_fn(|i| i as u16 * 3);
+  device.set_trackpad_counts(&base, &deltas);
+
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config());
+  let mut session = controller.begin_setup();
+  block_on(session.initialize()).unwrap();
+  block_on(session.enter_manual_control()).unwrap();
+  // Channel 6 (rx 2, tx 1) stuck in touch.
+  device.set_register(Reg::TouchState0 as u8, 1 << 6);
+  let snapshot = block_on(session.snapshot()).unwrap();
+  block_on(session.finish()).unwrap();
+
+  assert_eq!((snapshot.rx_count, snapshot.tx_count), (4, 3));
+  assert_eq!(snapshot.trackpad_base_targets[..12], base);
+  assert_eq!(snapshot.trackpad_deltas[..12], deltas);
+  assert_eq!(snapshot.trackpad_deltas[12], 0);
+  assert!(snapshot.touch_map.active().eq([(2, 1)]));
+  assert!(matches!(device.interrupt_mode(), InterruptMode::Event));
+}
+
+#[test]
+fn channel_matrix_clamps_to_stored_channels() {
+  let matrix = ChannelMatrix::new([1u16; 42], 7, 7);
+  assert_eq!(matrix.as_slice().len(), 42);
+  assert_eq!(matrix.row(5).map(<[u16]>::len), Some(7));
+  assert_eq!(matrix.row(6).map(<[u16]>::len), Some(0));
+  assert_eq!(matrix.get(0, 6), None);
+  assert_eq!(matrix.rows().count(), 7);
+}
+
+#[test]
+fn capture_frame_bursts_every_extended_page() {
+  let device = Simulator::new();
+  let mut controller = streaming(&device);
+  let counts: [u16; 12] = core::array::from_fn(|i| 1000 + i as u16);
+  let references: [u16; 12] = core::array::from_fn(|i| 990 + i as u16);
+  let deltas: [u16; 12] = core::array::from_fn(|i| (i as i16 - 4) as u16);
+  let compensation: [u16; 12] = core::array::from_fn(|i| 500 + i as u16);
+  device.set_ext_page(0xE000, &counts);
+  device.set_ext_page(0xE100, &references);
+  device.set_ext_page(0xE200, &deltas);
+  device.set_ext_page(0xE300, &compensation);
+
+  let before = device.transactions();
+  let frame = block_on(controller.capture_frame()).unwrap();
+  assert_eq!(device.transactions() - before, 1);
+
+  assert_eq!((frame.counts.rx_count(), frame.counts.tx_count()), (4, 3));
+  assert_eq!(frame.counts.as_slice(), counts);
+  assert_eq!(frame.references.get(3, 0), Some(993));
+  assert_eq!(frame.deltas.row(0), Some(&[-4, -3, -2, -1][..]));
+  assert_eq!(frame.deltas.get(1, 2), Some(5));
+  assert_eq!(frame.compensation.rows().last(), Some(&compensation[8..]));
+  assert_eq!(frame.counts.get(4, 0), None);
+}
+
+#[test]
+fn reseed_refreshes_references_without_ati() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let tunes = device.auto_tunes();
+
+  assert_eq!(block_on(controller.reseed_trackpad()).unwrap(), Calibration::Settled);
+  assert_eq!(block_on(controller.reseed_alp()).unwrap(), Calibration::Settled);
+  assert_eq!(device.reseeds(), (1, 1));
+  assert_eq!(device.auto_tunes(), tunes);
+}
+
+#[test]
+fn reseed_reports_contacts_present_during_the_reseed() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  device.push(Report::touch(42, 24));
+
+  let calibration = block_on(controller.reseed_trackpad()).unwrap();
+  assert!(matches!(calibration, Calibration::Disturbed(info) if info.num_fingers == 1));
+}
+
+#[test]
+fn recalibrate_retunes_both_engines_and_reports_errors() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let (trackpad, alp) = device.auto_tunes();
+
+  assert_eq!(block_on(controller.recalibrate(Recalibration::Retune)).unwrap(), Calibration::Settled);
+  assert_eq!(device.auto_tunes(), (trackpad + 1, alp + 1));
+
+  let info = device.register(Reg::InfoFlags as u8);
+  device.set_register(Reg::InfoFlags as u8, info | AUTO_TUNE_ERROR);
+  let calibration = block_on(controller.recalibrate(Recalibration::Retune)).unwrap();
+  assert!(matches!(calibration, Calibration::AutoTuneError(info) if info.auto_tuning_error));
+
+  device.stall_auto_tune(true);
+  let result = block_on(controller.recalibrate(Recalibration::Retune));
+  assert!(matches!(result, Err(Error::AutoTuneTimeout)));
+}
+
+#[test]
+fn recalibrate_waits_for_engines_finishing_on_different_cycles() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  device.set_alp_auto_tune_cycles(7);
+  let (trackpad, alp) = device.auto_tunes();
+
+  assert_eq!(block_on(controller.recalibrate(Recalibration::Retune)).unwrap(), Calibration::Settled);
+  assert_eq!(device.auto_tunes(), (trackpad + 1, alp + 1));
+  // Both routines ran to completion before the outcome was read.
+  assert_eq!(device.register(Reg::InfoFlags as u8) & RE_ATI, 0);
+  assert_ne!(device.register(Reg::InfoFlags as u8) & ALP_RE_ATI, 0);
+}
+
+#[test]
+fn production_test_samples_counts_in_tx_test_configuration() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  // Normal counts are healthy; the Tx test shows Tx9 (row 1) shorted.
+  device.set_ext_page(0xE000, &[600; 12]);
+  let mut counts = [600; 12];
+  counts[4..8].fill(12);
+  device.set_tx_test_counts(&counts);
+
+  let limits = ProductionLimits::new(CountRange::new(300, 1200));
+  let report = block_on(controller.production_test(&limits)).unwrap();
+  assert!(!report.passed());
+  assert!(report.failing_tx().eq([Pin::Tx9]));
+  assert_eq!(report.failing_rx().count(), 4);
+  assert_eq!(device.register(Reg::SysControl as u8) & TX_TEST, 0);
+  assert_eq!(device.reseeds(), (1, 0));
+
+  device.set_tx_test_counts(&[600; 12]);
+  assert!(block_on(controller.production_test(&limits)).unwrap().passed());
+}
+
+#[test]
+fn production_test_restores_sensing_when_sampling_fails() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let limits = ProductionLimits::new(CountRange::new(300, 1200));
+
+  device.fail_ext_reads(1);
+  assert!(matches!(block_on(controller.production_test(&limits)), Err(Error::I2c(_))));
+  assert_eq!(device.register(Reg::SysControl as u8) & TX_TEST, 0);
+  assert_eq!(device.reseeds(), (1, 0));
+
+  block_on(controller.suspend(Suspend::Deep)).unwrap();
+  assert!(matches!(block_on(controller.production_test(&limits)), Err(Error::Suspended)));
+}
+
+#[test]
+fn event_triggers_and_watchdog_are_staged_and_changed_at_runtime() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let staged = config()
+    .with_event_triggers(EventTriggers::new(false, true, false, true, false))
+    .with_watchdog(false);
+  let mut controller = Iqs7211e::new(i2c, rdy, staged);
+  assert!(block_on(controller.initialize()).unwrap());
+  let settings = device.register(Reg::ConfigSettings as u8);
+  assert_eq!(settings & (GESTURE_EVENT | TRACKPAD_EVENT | ALP_EVENT), TRACKPAD_EVENT | ALP_EVENT);
+  assert_eq!(settings & WATCHDOG, 0);
+
+  block_on(controller.set_event_triggers(EventTriggers::default())).unwrap();
+  block_on(controller.set_watchdog(true)).unwrap();
+  let settings = device.register(Reg::ConfigSettings as u8);
+  assert_eq!(settings & (GESTURE_EVENT | TRACKPAD_EVENT | ALP_EVENT), GESTURE_EVENT | TRACKPAD_EVENT);
+  assert_ne!(settings & WATCHDOG, 0);
+  assert_eq!(controller.config().event_triggers, EventTriggers::default());
+  assert!(controller.config().watchdog);
+}
+
+#[test]
+fn end_comms_windows_are_closed_by_the_driver() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let mut controller = Iqs7211e::new(i2c, rdy, config().with_end_comms(true));
+  assert!(block_on(controller.initialize()).unwrap());
+  assert_ne!(device.register(Reg::ConfigSettings as u8) & END_COMMS, 0);
+
+  device.push(Report::touch(42, 24));
+  device.push(Report::release());
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+  assert!(!device.window_open());
+
+  assert_eq!(block_on(controller.recalibrate(Recalibration::Retune)).unwrap(), Calibration::Settled);
+  block_on(controller.set_end_comms(false)).unwrap();
+  assert_eq!(device.register(Reg::ConfigSettings as u8) & END_COMMS, 0);
+  assert!(!device.window_open());
+}
+
+#[test]
+fn deep_suspend_holds_reports_until_resume() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  block_on(controller.suspend(Suspend::Deep)).unwrap();
+  assert_ne!(device.register(Reg::SysControl as u8) & SUSPEND, 0);
+  assert_eq!(controller.suspended(), Some(Suspend::Deep));
+
+  device.push(Report::touch(42, 24));
+  assert!(matches!(block_on(controller.next_event()), Err(Error::Suspended)));
+
+  block_on(controller.resume()).unwrap();
+  assert_eq!(device.register(Reg::SysControl as u8) & SUSPEND, 0);
+  assert_eq!(controller.suspended(), None);
+  assert!(matches!(block_on(controller.next_event()).unwrap(), Event::Touch(..)));
+}
+
+#[test]
+fn suspend_wakes_on_proximity_and_restores_triggers() {
+  let device = Simulator::new();
+  let mut controller = streaming(&device);
+  let settings = device.register(Reg::ConfigSettings as u8);
+  block_on(controller.suspend(Suspend::WakeOnProximity)).unwrap();
+  assert!(matches!(device.interrupt_mode(), InterruptMode::Event));
+
+  // Touches no longer open a window; only the approaching hand does.
+  device.push(Report::touch(42, 24));
+  device.push(Report::release().with_proximity(true));
+  assert_eq!(block_on(controller.next_event()).unwrap(), Event::Proximity(true));
+  assert_eq!(controller.suspended(), None);
+  assert_eq!(device.register(Reg::ConfigSettings as u8), settings);
+}
+
+#[test]
+fn power_manager_forces_low_power_while_the_system_idles() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let mut power = PowerManager::default();
+
+  let idle = block_on(power.on_system_idle(&mut controller)).unwrap().unwrap();
+  assert_eq!(idle.control, PowerControl::Forced(ChargeMode::LowPower2));
+  assert_eq!(idle.to, ChargeMode::LowPower2);
+  assert_ne!(device.register(Reg::ConfigSettings as u8) & MANUAL_CONTROL, 0);
+  assert_eq!(device.register(Reg::SysControl as u8) & CHARGE_MODE_MASK, ChargeMode::LowPower2 as u16);
+
+  // Nothing to change, so the bus is left alone.
+  let transactions = device.transactions();
+  assert_eq!(block_on(power.on_system_idle(&mut controller)).unwrap(), None);
+  assert_eq!(device.transactions(), transactions);
+
+  let active = block_on(power.on_system_active(&mut controller)).unwrap().unwrap();
+  assert_eq!(active.control, PowerControl::Automatic);
+  assert_eq!(active.from, Some(ChargeMode::LowPower2));
+  assert_eq!(device.register(Reg::ConfigSettings as u8) & MANUAL_CONTROL, 0);
+  assert_eq!(power.system(), SystemState::Active);
+}
+
+#[test]
+fn power_control_is_refused_while_suspended() {
+  let device = Simulator::new();
+  let mut controller = ready(&device);
+  let mut power = PowerManager::default();
+  block_on(controller.suspend(Suspend::Deep)).unwrap();
+
+  assert!(matches!(block_on(power.on_system_idle(&mut controller)), Err(Error::Suspended)));
+  assert_eq!(device.register(Reg::ConfigSettings as u8) & MANUAL_CONTROL, 0);
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_driver_runs_against_simulator() {
+  let device = Simulator::new();
+  let (i2c, rdy) = device.split();
+  let mut controller = crate::blocking::Iqs7211e::new(i2c, rdy, config());
+  assert!(controller.initialize().unwrap());
+
+  device.push(Report::touch(42, 24));
+  assert!(matches!(controller.next_event().unwrap(), Event::Touch(..)));
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_driver_times_out_when_rdy_is_stuck() {
+  let device = Simulator::new();
+  device.set_rdy_stuck(true);
+  let (i2c, rdy) = device.split();
+  let mut controller = crate::blocking::Iqs7211e::new(i2c, rdy, config())
+    .with_delay(device.delay())
+    .with_deadlines(Deadlines::new(Some(1), 20));
+
+  assert!(matches!(controller.initialize(), Err(Error::RdyTimeout)));
+  assert_eq!(device.elapsed_ns(), 1_000_000);
+}
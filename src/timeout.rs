@@ -0,0 +1,56 @@
+use embedded_hal_async::delay::DelayNs;
+
+/// Default RDY deadline applied once a delay provider is attached with
+/// [`Iqs7211e::with_delay`](crate::Iqs7211e::with_delay).
+pub(crate) const DEFAULT_COMM_WINDOW_MS: u32 = 500;
+
+/// Upper bounds used to detect an unresponsive controller.
+///
+/// Every communication window opened by the driver (outside of waiting for
+/// Event-mode activity) is bounded by `comm_window_ms`, and every multi-cycle
+/// bring-up phase (reset handshake, ATI) by `max_polls` windows, so
+/// [`Iqs7211e::initialize`](crate::Iqs7211e::initialize) always returns when a
+/// delay provider is attached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadlines {
+  /// Longest wait for RDY to open a communication window, in milliseconds.
+  /// `None` waits forever. Must exceed the slowest report rate in use.
+  pub comm_window_ms: Option<u32>,
+  /// Number of communication windows polled while waiting for a reset or an
+  /// ATI routine to complete before giving up.
+  pub max_polls: u16,
+}
+
+impl Deadlines {
+  /// No RDY deadline and 250 polls, applied until
+  /// [`Iqs7211e::with_delay`](crate::Iqs7211e::with_delay) attaches a delay
+  /// provider.
+  const UNTIMED: Self = Self::new(None, 250);
+
+  /// Limits waiting at most `comm_window_ms` for each window, or forever with
+  /// `None`, and `max_polls` windows for each bring-up phase.
+  ///
+  /// The window deadline needs a delay provider to measure it; without one it
+  /// is kept but not enforced until
+  /// [`Iqs7211e::with_delay`](crate::Iqs7211e::with_delay) attaches one.
+  pub const fn new(comm_window_ms: Option<u32>, max_polls: u16) -> Self {
+    Self { comm_window_ms, max_polls }
+  }
+}
+
+impl Default for Deadlines {
+  fn default() -> Self {
+    Self::UNTIMED
+  }
+}
+
+/// Placeholder delay provider used until one is attached with
+/// [`Iqs7211e::with_delay`](crate::Iqs7211e::with_delay).
+///
+/// Delays complete immediately, so no RDY deadline is enforced.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoDelay;
+
+impl DelayNs for NoDelay {
+  async fn delay_ns(&mut self, _ns: u32) {}
+}
@@ -0,0 +1,15 @@
+use core::task::{RawWaker, RawWakerVTable, Waker};
+
+const NOOP_VTABLE: RawWakerVTable = RawWakerVTable::new(noop_clone, noop, noop, noop);
+
+unsafe fn noop_clone(_: *const ()) -> RawWaker {
+  RawWaker::new(core::ptr::null(), &NOOP_VTABLE)
+}
+
+unsafe fn noop(_: *const ()) {}
+
+/// Waker for executors that poll in place and never park.
+pub(crate) fn noop_waker() -> Waker {
+  // SAFETY: the vtable functions ignore the data pointer and never dereference it.
+  unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &NOOP_VTABLE)) }
+}